@@ -0,0 +1,92 @@
+//! Counts the allocations made while lexing a large generated script.
+//!
+//! `borrowed` is the lexer as it is; `owned` converts every token with
+//! `into_owned`, which is what lexing cost when each token carried its own
+//! `String`. Run with `cargo bench --bench lexer`.
+
+use interpreter::lexer::Lexer;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const SNIPPET: &str = r#"
+/// Adds two numbers.
+let add_0 = fn(x, y) { x + y; };
+let total_0 = add_0(1_000, 0xFF) * 2.5e3;
+if (total_0 >= 10 && total_0 != 99) {
+    return "plain string";
+} else {
+    return "escaped\tstring\n";
+}
+"#;
+
+fn script(copies: usize) -> String {
+    let mut input = String::with_capacity(SNIPPET.len() * copies);
+
+    for _ in 0..copies {
+        input.push_str(SNIPPET);
+    }
+
+    input
+}
+
+fn measure(name: &str, lex: impl Fn() -> usize) {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    BYTES.store(0, Ordering::Relaxed);
+
+    let start = Instant::now();
+    let tokens = lex();
+    let elapsed = start.elapsed();
+
+    println!(
+        "{:<9} {} tokens, {} allocations, {} bytes allocated, {:?}",
+        name,
+        tokens,
+        ALLOCATIONS.load(Ordering::Relaxed),
+        BYTES.load(Ordering::Relaxed),
+        elapsed
+    );
+}
+
+fn main() {
+    let input = script(10_000);
+
+    measure("borrowed", || {
+        let mut tokens = 0;
+        for token in Lexer::new(&input) {
+            black_box(token);
+            tokens += 1;
+        }
+        tokens
+    });
+
+    measure("owned", || {
+        let mut tokens = 0;
+        for token in Lexer::new(&input) {
+            black_box(token.into_owned());
+            tokens += 1;
+        }
+        tokens
+    });
+}
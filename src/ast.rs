@@ -1,5 +1,6 @@
 use crate::tokens::Token;
 
+#[derive(Clone)]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
@@ -14,14 +15,23 @@ impl Node for Program {
     }
 
     fn string(&self) -> String {
-        let mut out = String::new();
+        statements_string(&self.statements)
+    }
+}
 
-        for s in &self.statements {
-            out.push_str(&s.string());
-        }
+/// Prints `statements` one after another. A statement that ends in `}` is
+/// followed by a space, so it doesn't run into the next one.
+fn statements_string(statements: &[Statement]) -> String {
+    let mut out = String::new();
 
-        out
+    for s in statements {
+        if out.ends_with('}') {
+            out.push(' ');
+        }
+        out.push_str(&s.string());
     }
+
+    out
 }
 
 pub trait Node {
@@ -29,10 +39,24 @@ pub trait Node {
     fn string(&self) -> String;
 }
 
+#[derive(Clone)]
 pub enum Expression {
     Identifier(Identifier),
     IntegerLiteral(IntegerLiteral),
+    FloatLiteral(FloatLiteral),
+    StringLiteral(StringLiteral),
+    CharLiteral(CharLiteral),
+    Boolean(Boolean),
+    InterpolatedString(InterpolatedString),
     PrefixExpression(PrefixExpression),
+    InfixExpression(InfixExpression),
+    AssignExpression(AssignExpression),
+    IfExpression(Box<IfExpression>),
+    FunctionLiteral(Box<FunctionLiteral>),
+    CallExpression(CallExpression),
+    ArrayLiteral(ArrayLiteral),
+    IndexExpression(IndexExpression),
+    HashLiteral(HashLiteral),
 }
 
 impl Node for Expression {
@@ -40,7 +64,20 @@ impl Node for Expression {
         match self {
             Expression::Identifier(identifier) => identifier.token_literal(),
             Expression::IntegerLiteral(integer_literal) => integer_literal.token_literal(),
+            Expression::FloatLiteral(float_literal) => float_literal.token_literal(),
+            Expression::StringLiteral(string_literal) => string_literal.token_literal(),
+            Expression::CharLiteral(char_literal) => char_literal.token_literal(),
+            Expression::Boolean(boolean) => boolean.token_literal(),
+            Expression::InterpolatedString(interpolated) => interpolated.token_literal(),
             Expression::PrefixExpression(prefix_expression) => prefix_expression.token_literal(),
+            Expression::InfixExpression(infix_expression) => infix_expression.token_literal(),
+            Expression::AssignExpression(assign_expression) => assign_expression.token_literal(),
+            Expression::IfExpression(if_expression) => if_expression.token_literal(),
+            Expression::FunctionLiteral(function_literal) => function_literal.token_literal(),
+            Expression::CallExpression(call_expression) => call_expression.token_literal(),
+            Expression::ArrayLiteral(array_literal) => array_literal.token_literal(),
+            Expression::IndexExpression(index_expression) => index_expression.token_literal(),
+            Expression::HashLiteral(hash_literal) => hash_literal.token_literal(),
         }
     }
 
@@ -48,19 +85,151 @@ impl Node for Expression {
         match self {
             Expression::Identifier(identifier) => identifier.string(),
             Expression::IntegerLiteral(integer_literal) => integer_literal.string(),
+            Expression::FloatLiteral(float_literal) => float_literal.string(),
+            Expression::StringLiteral(string_literal) => string_literal.string(),
+            Expression::CharLiteral(char_literal) => char_literal.string(),
+            Expression::Boolean(boolean) => boolean.string(),
+            Expression::InterpolatedString(interpolated) => interpolated.string(),
             Expression::PrefixExpression(prefix_expression) => prefix_expression.string(),
+            Expression::InfixExpression(infix_expression) => infix_expression.string(),
+            Expression::AssignExpression(assign_expression) => assign_expression.string(),
+            Expression::IfExpression(if_expression) => if_expression.string(),
+            Expression::FunctionLiteral(function_literal) => function_literal.string(),
+            Expression::CallExpression(call_expression) => call_expression.string(),
+            Expression::ArrayLiteral(array_literal) => array_literal.string(),
+            Expression::IndexExpression(index_expression) => index_expression.string(),
+            Expression::HashLiteral(hash_literal) => hash_literal.string(),
         }
     }
 }
 
 impl Expression {
     fn expression_node(&self) {}
+
+    /// The token the expression was read from, e.g. the operator of an infix
+    /// expression.
+    pub fn token(&self) -> &Token<'static> {
+        match self {
+            Expression::Identifier(identifier) => &identifier.token,
+            Expression::IntegerLiteral(integer_literal) => &integer_literal.token,
+            Expression::FloatLiteral(float_literal) => &float_literal.token,
+            Expression::StringLiteral(string_literal) => &string_literal.token,
+            Expression::CharLiteral(char_literal) => &char_literal.token,
+            Expression::Boolean(boolean) => &boolean.token,
+            Expression::InterpolatedString(interpolated) => &interpolated.token,
+            Expression::PrefixExpression(prefix_expression) => &prefix_expression.token,
+            Expression::InfixExpression(infix_expression) => &infix_expression.token,
+            Expression::AssignExpression(assign_expression) => &assign_expression.token,
+            Expression::IfExpression(if_expression) => &if_expression.token,
+            Expression::FunctionLiteral(function_literal) => &function_literal.token,
+            Expression::CallExpression(call_expression) => &call_expression.token,
+            Expression::ArrayLiteral(array_literal) => &array_literal.token,
+            Expression::IndexExpression(index_expression) => &index_expression.token,
+            Expression::HashLiteral(hash_literal) => &hash_literal.token,
+        }
+    }
+
+    /// Calls `f` on every token in the expression.
+    pub fn for_each_token_mut<F: FnMut(&mut Token<'static>)>(&mut self, f: &mut F) {
+        match self {
+            Expression::Identifier(Identifier { token, value: _ })
+            | Expression::IntegerLiteral(IntegerLiteral { token, value: _ })
+            | Expression::FloatLiteral(FloatLiteral { token, value: _ })
+            | Expression::StringLiteral(StringLiteral { token, value: _ })
+            | Expression::CharLiteral(CharLiteral { token, value: _ })
+            | Expression::Boolean(Boolean { token, value: _ }) => f(token),
+            Expression::InterpolatedString(InterpolatedString { token, parts }) => {
+                f(token);
+                for part in parts {
+                    if let InterpolationPart::Expression(expr) = part {
+                        expr.for_each_token_mut(f);
+                    }
+                }
+            }
+            Expression::PrefixExpression(PrefixExpression {
+                token,
+                operator: _,
+                right,
+            }) => {
+                f(token);
+                right.for_each_token_mut(f);
+            }
+            Expression::InfixExpression(InfixExpression {
+                token,
+                left,
+                operator: _,
+                right,
+            }) => {
+                f(token);
+                left.for_each_token_mut(f);
+                right.for_each_token_mut(f);
+            }
+            Expression::AssignExpression(AssignExpression {
+                token,
+                target,
+                operator: _,
+                value,
+            }) => {
+                f(token);
+                target.for_each_token_mut(f);
+                value.for_each_token_mut(f);
+            }
+            Expression::IfExpression(if_expression) => if_expression.for_each_token_mut(f),
+            Expression::FunctionLiteral(function_literal) => {
+                let FunctionLiteral {
+                    token,
+                    parameters,
+                    body,
+                } = function_literal.as_mut();
+
+                f(token);
+                for Identifier { token, value: _ } in parameters {
+                    f(token);
+                }
+                body.for_each_token_mut(f);
+            }
+            Expression::CallExpression(CallExpression {
+                token,
+                function,
+                arguments,
+            }) => {
+                f(token);
+                function.for_each_token_mut(f);
+                for argument in arguments {
+                    argument.for_each_token_mut(f);
+                }
+            }
+            Expression::ArrayLiteral(ArrayLiteral { token, elements }) => {
+                f(token);
+                for element in elements {
+                    element.for_each_token_mut(f);
+                }
+            }
+            Expression::IndexExpression(IndexExpression { token, left, index }) => {
+                f(token);
+                left.for_each_token_mut(f);
+                index.for_each_token_mut(f);
+            }
+            Expression::HashLiteral(HashLiteral { token, pairs }) => {
+                f(token);
+                for (key, value) in pairs {
+                    key.for_each_token_mut(f);
+                    value.for_each_token_mut(f);
+                }
+            }
+        }
+    }
 }
 
+#[derive(Clone)]
 pub enum Statement {
     Let(LetStatement),
     Return(ReturnStatement),
     Expression(ExpressionStatement),
+    While(WhileStatement),
+    For(ForStatement),
+    Break(BreakStatement),
+    Continue(ContinueStatement),
 }
 
 impl Node for Statement {
@@ -69,6 +238,10 @@ impl Node for Statement {
             Statement::Let(let_statement) => let_statement.token_literal(),
             Statement::Return(return_statement) => return_statement.token_literal(),
             Statement::Expression(expression_statement) => expression_statement.token_literal(),
+            Statement::While(while_statement) => while_statement.token_literal(),
+            Statement::For(for_statement) => for_statement.token_literal(),
+            Statement::Break(break_statement) => break_statement.token_literal(),
+            Statement::Continue(continue_statement) => continue_statement.token_literal(),
         }
     }
 
@@ -77,22 +250,96 @@ impl Node for Statement {
             Statement::Let(let_statement) => let_statement.string(),
             Statement::Return(return_statement) => return_statement.string(),
             Statement::Expression(expression_statement) => expression_statement.string(),
+            Statement::While(while_statement) => while_statement.string(),
+            Statement::For(for_statement) => for_statement.string(),
+            Statement::Break(break_statement) => break_statement.string(),
+            Statement::Continue(continue_statement) => continue_statement.string(),
         }
     }
 }
 
 impl Statement {
     fn statement_node(&self) {}
+
+    /// The statement's first token, apart from doc comments.
+    pub fn token(&self) -> &Token<'static> {
+        match self {
+            Statement::Let(let_statement) => &let_statement.token,
+            Statement::Return(return_statement) => &return_statement.token,
+            Statement::Expression(expression_statement) => &expression_statement.token,
+            Statement::While(while_statement) => &while_statement.token,
+            Statement::For(for_statement) => &for_statement.token,
+            Statement::Break(break_statement) => &break_statement.token,
+            Statement::Continue(continue_statement) => &continue_statement.token,
+        }
+    }
+
+    /// Calls `f` on every token in the statement, e.g. to move its spans
+    /// without parsing it again.
+    pub fn for_each_token_mut<F: FnMut(&mut Token<'static>)>(&mut self, f: &mut F) {
+        match self {
+            Statement::Let(LetStatement {
+                token,
+                doc: _,
+                name,
+                value,
+            }) => {
+                f(token);
+                f(&mut name.token);
+                if let Some(value) = value {
+                    value.for_each_token_mut(f);
+                }
+            }
+            Statement::Return(ReturnStatement {
+                token,
+                return_value,
+            }) => {
+                f(token);
+                if let Some(value) = return_value {
+                    value.for_each_token_mut(f);
+                }
+            }
+            Statement::Expression(ExpressionStatement { token, expression }) => {
+                f(token);
+                if let Some(expr) = expression {
+                    expr.for_each_token_mut(f);
+                }
+            }
+            Statement::While(WhileStatement {
+                token,
+                condition,
+                body,
+            }) => {
+                f(token);
+                condition.for_each_token_mut(f);
+                body.for_each_token_mut(f);
+            }
+            Statement::For(ForStatement {
+                token,
+                variable,
+                iterable,
+                body,
+            }) => {
+                f(token);
+                f(&mut variable.token);
+                iterable.for_each_token_mut(f);
+                body.for_each_token_mut(f);
+            }
+            Statement::Break(BreakStatement { token })
+            | Statement::Continue(ContinueStatement { token }) => f(token),
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct Identifier {
-    pub token: Token,
+    pub token: Token<'static>,
     pub value: String,
 }
 
 impl Node for Identifier {
     fn token_literal(&self) -> String {
-        self.token.literal.clone()
+        self.token.literal.to_string()
     }
 
     fn string(&self) -> String {
@@ -112,15 +359,18 @@ impl Statement {
     }
 }
 
+#[derive(Clone)]
 pub struct LetStatement {
-    pub token: Token,
+    pub token: Token<'static>,
+    /// Text of the `///` doc comments directly above the statement.
+    pub doc: Option<String>,
     pub name: Identifier,
     pub value: Option<Expression>,
 }
 
 impl Node for LetStatement {
     fn token_literal(&self) -> String {
-        self.token.literal.clone()
+        self.token.literal.to_string()
     }
 
     fn string(&self) -> String {
@@ -141,23 +391,24 @@ impl Node for LetStatement {
     }
 }
 
+#[derive(Clone)]
 pub struct ReturnStatement {
-    pub token: Token,
+    pub token: Token<'static>,
     pub return_value: Option<Expression>,
 }
 
 impl Node for ReturnStatement {
     fn token_literal(&self) -> String {
-        self.token.literal.clone()
+        self.token.literal.to_string()
     }
 
     fn string(&self) -> String {
         let mut out = String::new();
 
         out.push_str(&self.token_literal());
-        out.push_str(" ");
 
         if let Some(return_value) = &self.return_value {
+            out.push_str(" ");
             out.push_str(&return_value.string());
         }
 
@@ -167,14 +418,15 @@ impl Node for ReturnStatement {
     }
 }
 
+#[derive(Clone)]
 pub struct ExpressionStatement {
-    pub token: Token,
+    pub token: Token<'static>,
     pub expression: Option<Expression>,
 }
 
 impl Node for ExpressionStatement {
     fn token_literal(&self) -> String {
-        self.token.literal.clone()
+        self.token.literal.to_string()
     }
 
     fn string(&self) -> String {
@@ -186,30 +438,158 @@ impl Node for ExpressionStatement {
     }
 }
 
+#[derive(Clone)]
 pub struct IntegerLiteral {
-    pub token: Token,
+    pub token: Token<'static>,
     pub value: i64,
 }
 
 impl Node for IntegerLiteral {
     fn token_literal(&self) -> String {
-        self.token.literal.clone()
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+#[derive(Clone)]
+pub struct FloatLiteral {
+    pub token: Token<'static>,
+    pub value: f64,
+}
+
+impl Node for FloatLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
     }
 
     fn string(&self) -> String {
-        self.token.literal.clone()
+        self.token.literal.to_string()
     }
 }
 
+#[derive(Clone)]
+pub struct StringLiteral {
+    pub token: Token<'static>,
+    pub value: String,
+}
+
+impl Node for StringLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!("\"{}\"", escape(&self.value, '"'))
+    }
+}
+
+#[derive(Clone)]
+pub struct CharLiteral {
+    pub token: Token<'static>,
+    pub value: char,
+}
+
+impl Node for CharLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!("'{}'", escape(&self.value.to_string(), '\''))
+    }
+}
+
+#[derive(Clone)]
+pub struct Boolean {
+    pub token: Token<'static>,
+    pub value: bool,
+}
+
+impl Node for Boolean {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+#[derive(Clone)]
+pub enum InterpolationPart {
+    Literal(String),
+    Expression(Expression),
+}
+
+/// A string literal with `${...}` pieces, e.g. `"hello ${name}"`.
+#[derive(Clone)]
+pub struct InterpolatedString {
+    pub token: Token<'static>,
+    pub parts: Vec<InterpolationPart>,
+}
+
+impl Node for InterpolatedString {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::from("\"");
+
+        for part in &self.parts {
+            match part {
+                InterpolationPart::Literal(text) => out.push_str(&escape(text, '"')),
+                InterpolationPart::Expression(expr) => {
+                    out.push_str("${");
+                    out.push_str(&expr.string());
+                    out.push('}');
+                }
+            }
+        }
+
+        out.push('"');
+        out
+    }
+}
+
+/// Escapes `value` so that it lexes back to the same text between `quote`s.
+fn escape(value: &str, quote: char) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            ch if ch == quote => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            '$' if chars.peek() == Some(&'{') => out.push_str("\\$"),
+            ch if ch.is_control() => out.push_str(&format!("\\u{{{:x}}}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+
+    out
+}
+
+#[derive(Clone)]
 pub struct PrefixExpression {
-    pub token: Token,
+    pub token: Token<'static>,
     pub operator: String,
     pub right: Box<Expression>,
 }
 
 impl Node for PrefixExpression {
     fn token_literal(&self) -> String {
-        self.token.literal.clone()
+        self.token.literal.to_string()
     }
 
     fn string(&self) -> String {
@@ -217,10 +597,329 @@ impl Node for PrefixExpression {
     }
 }
 
+#[derive(Clone)]
+pub struct InfixExpression {
+    pub token: Token<'static>,
+    pub left: Box<Expression>,
+    pub operator: String,
+    pub right: Box<Expression>,
+}
+
+impl Node for InfixExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!(
+            "({} {} {})",
+            self.left.string(),
+            self.operator,
+            self.right.string()
+        )
+    }
+}
+
+/// A compound assignment such as `x += 1`.
+#[derive(Clone)]
+pub struct AssignExpression {
+    pub token: Token<'static>,
+    pub target: Box<Expression>,
+    pub operator: String,
+    pub value: Box<Expression>,
+}
+
+impl Node for AssignExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!(
+            "({} {} {})",
+            self.target.string(),
+            self.operator,
+            self.value.string()
+        )
+    }
+}
+
+/// `{ ... }`: the statements of an `if` branch or function body.
+#[derive(Clone)]
+pub struct BlockStatement {
+    pub token: Token<'static>,
+    pub statements: Vec<Statement>,
+}
+
+impl Node for BlockStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        if self.statements.is_empty() {
+            return String::from("{}");
+        }
+
+        format!("{{ {} }}", statements_string(&self.statements))
+    }
+}
+
+impl BlockStatement {
+    pub fn for_each_token_mut<F: FnMut(&mut Token<'static>)>(&mut self, f: &mut F) {
+        let BlockStatement { token, statements } = self;
+
+        f(token);
+        for statement in statements {
+            statement.for_each_token_mut(f);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IfExpression {
+    pub token: Token<'static>,
+    pub condition: Box<Expression>,
+    pub consequence: BlockStatement,
+    pub alternative: Option<Alternative>,
+}
+
+impl IfExpression {
+    pub fn for_each_token_mut<F: FnMut(&mut Token<'static>)>(&mut self, f: &mut F) {
+        let IfExpression {
+            token,
+            condition,
+            consequence,
+            alternative,
+        } = self;
+
+        f(token);
+        condition.for_each_token_mut(f);
+        consequence.for_each_token_mut(f);
+
+        match alternative {
+            Some(Alternative::Block(block)) => block.for_each_token_mut(f),
+            Some(Alternative::If(if_expression)) => if_expression.for_each_token_mut(f),
+            None => {}
+        }
+    }
+}
+
+/// What follows `else`: a block, or another `if` in an `else if` chain.
+#[derive(Clone)]
+pub enum Alternative {
+    Block(BlockStatement),
+    If(Box<IfExpression>),
+}
+
+impl Node for IfExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        let mut out = format!(
+            "if ({}) {}",
+            self.condition.string(),
+            self.consequence.string()
+        );
+
+        match &self.alternative {
+            Some(Alternative::Block(block)) => out.push_str(&format!(" else {}", block.string())),
+            Some(Alternative::If(if_expression)) => {
+                out.push_str(&format!(" else {}", if_expression.string()))
+            }
+            None => {}
+        }
+
+        out
+    }
+}
+
+/// `fn(x, y) { ... }`
+#[derive(Clone)]
+pub struct FunctionLiteral {
+    pub token: Token<'static>,
+    pub parameters: Vec<Identifier>,
+    pub body: BlockStatement,
+}
+
+impl Node for FunctionLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        let parameters: Vec<String> = self.parameters.iter().map(|p| p.string()).collect();
+
+        format!(
+            "{}({}) {}",
+            self.token_literal(),
+            parameters.join(", "),
+            self.body.string()
+        )
+    }
+}
+
+/// `add(1, 2)`, where `function` is any expression that evaluates to a
+/// function: an identifier, a function literal, another call, ...
+#[derive(Clone)]
+pub struct CallExpression {
+    pub token: Token<'static>,
+    pub function: Box<Expression>,
+    pub arguments: Vec<Expression>,
+}
+
+impl Node for CallExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        let arguments: Vec<String> = self.arguments.iter().map(|a| a.string()).collect();
+
+        format!("{}({})", self.function.string(), arguments.join(", "))
+    }
+}
+
+/// `[1, 2 * 2, "three"]`
+#[derive(Clone)]
+pub struct ArrayLiteral {
+    pub token: Token<'static>,
+    pub elements: Vec<Expression>,
+}
+
+impl Node for ArrayLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        let elements: Vec<String> = self.elements.iter().map(|e| e.string()).collect();
+
+        format!("[{}]", elements.join(", "))
+    }
+}
+
+/// `left[index]`
+#[derive(Clone)]
+pub struct IndexExpression {
+    pub token: Token<'static>,
+    pub left: Box<Expression>,
+    pub index: Box<Expression>,
+}
+
+impl Node for IndexExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!("({}[{}])", self.left.string(), self.index.string())
+    }
+}
+
+/// `{"name": "x", 1: true}`, with the pairs kept in source order.
+#[derive(Clone)]
+pub struct HashLiteral {
+    pub token: Token<'static>,
+    pub pairs: Vec<(Expression, Expression)>,
+}
+
+impl Node for HashLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key.string(), value.string()))
+            .collect();
+
+        format!("{{{}}}", pairs.join(", "))
+    }
+}
+
+/// `while (condition) { ... }`
+#[derive(Clone)]
+pub struct WhileStatement {
+    pub token: Token<'static>,
+    pub condition: Expression,
+    pub body: BlockStatement,
+}
+
+impl Node for WhileStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!("while ({}) {}", self.condition.string(), self.body.string())
+    }
+}
+
+/// `for (variable in iterable) { ... }`
+#[derive(Clone)]
+pub struct ForStatement {
+    pub token: Token<'static>,
+    pub variable: Identifier,
+    pub iterable: Expression,
+    pub body: BlockStatement,
+}
+
+impl Node for ForStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!(
+            "for ({} in {}) {}",
+            self.variable.string(),
+            self.iterable.string(),
+            self.body.string()
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct BreakStatement {
+    pub token: Token<'static>,
+}
+
+impl Node for BreakStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!("{};", self.token_literal())
+    }
+}
+
+#[derive(Clone)]
+pub struct ContinueStatement {
+    pub token: Token<'static>,
+}
+
+impl Node for ContinueStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!("{};", self.token_literal())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Identifier;
     use crate::ast::Node;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
     use crate::tokens::Token;
 
     use super::LetStatement;
@@ -235,11 +934,14 @@ mod test {
             token: Token {
                 token_type: crate::tokens::TokenType::Let,
                 literal: "let".into(),
+                span: Default::default(),
             },
+            doc: None,
             name: super::Identifier {
                 token: Token {
                     token_type: crate::tokens::TokenType::Ident,
                     literal: "my_var".into(),
+                    span: Default::default(),
                 },
                 value: "my_var".into(),
             },
@@ -247,6 +949,7 @@ mod test {
                 token: Token {
                     token_type: crate::tokens::TokenType::Ident,
                     literal: "another_var".into(),
+                    span: Default::default(),
                 },
                 value: "another_var".into(),
             })),
@@ -265,4 +968,31 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_for_each_token_mut() -> Result<(), ()> {
+        // One of each kind of statement and expression the parser reads.
+        let input = "/// Doc.\nlet a = 1;\nreturn b;\nc += 2.5 * \"s ${'d'}\" - -(e) == true;\n\
+                     if (f) { g; } else if (h) {} else { i }\nfn(j, k) { l }(m, n);\n[o, p][q];\n{r: s};\nwhile (t) { break; }\nfor (u in v) { continue; }";
+        let mut program = Parser::new(Lexer::new(input)).parse_program();
+
+        let mut literals = Vec::new();
+        for statement in &mut program.statements {
+            statement.for_each_token_mut(&mut |token| literals.push(token.literal.to_string()));
+        }
+
+        let expected = [
+            "let", "a", "1", "return", "b", "c", "+=", "c", "==", "-", "*", "2.5", "s ", "d", "-",
+            "e", "true", "if", "if", "f", "{", "g", "g", "if", "h", "{", "{", "i", "i", "fn", "(",
+            "fn", "j", "k", "{", "l", "l", "m", "n", "[", "[", "[", "o", "p", "q", "{", "{", "r",
+            "s", "while", "t", "{", "break", "for", "u", "v", "{", "continue",
+        ];
+
+        if literals != expected {
+            println!("expected={:?} got={:?}", expected, literals);
+            return Err(());
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,114 @@
+use crate::object::{Builtin, Object};
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "ord",
+        func: ord,
+    },
+    Builtin {
+        name: "chr",
+        func: chr,
+    },
+];
+
+/// The builtin function called `name`, if there is one. Variables of the
+/// same name shadow it.
+pub fn lookup(name: &str) -> Option<Object> {
+    BUILTINS
+        .iter()
+        .find(|builtin| builtin.name == name)
+        .map(|builtin| Object::Builtin(*builtin))
+}
+
+fn wrong_arguments(name: &str, args: &[Object], want: &str) -> String {
+    let got: Vec<_> = args.iter().map(Object::type_name).collect();
+    format!(
+        "wrong arguments to `{}`: got ({}), want ({})",
+        name,
+        got.join(", "),
+        want
+    )
+}
+
+/// `ord(c)`: the Unicode code point of the char `c`.
+fn ord(args: &[Object]) -> Result<Object, String> {
+    match args {
+        [Object::Char(ch)] => Ok(Object::Integer(*ch as i64)),
+        _ => Err(wrong_arguments("ord", args, "CHAR")),
+    }
+}
+
+/// `chr(i)`: the char whose Unicode code point is `i`.
+fn chr(args: &[Object]) -> Result<Object, String> {
+    match args {
+        [Object::Integer(code)] => u32::try_from(*code)
+            .ok()
+            .and_then(char::from_u32)
+            .map(Object::Char)
+            .ok_or_else(|| format!("{} is not a Unicode code point", code)),
+        _ => Err(wrong_arguments("chr", args, "INTEGER")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::lookup;
+    use crate::object::Object;
+
+    fn call(name: &str, args: &[Object]) -> Result<Object, String> {
+        match lookup(name) {
+            Some(Object::Builtin(builtin)) => (builtin.func)(args),
+            other => Err(format!("no builtin {}: {:?}", name, other)),
+        }
+    }
+
+    #[test]
+    fn char_conversions() -> Result<(), ()> {
+        let tests = vec![
+            ("ord", vec![Object::Char('a')], Ok(Object::Integer(97))),
+            (
+                "ord",
+                vec![Object::Char('\u{1F600}')],
+                Ok(Object::Integer(0x1F600)),
+            ),
+            ("chr", vec![Object::Integer(233)], Ok(Object::Char('é'))),
+            (
+                "chr",
+                vec![Object::Integer(0xD800)],
+                Err(String::from("55296 is not a Unicode code point")),
+            ),
+            (
+                "chr",
+                vec![Object::Integer(-1)],
+                Err(String::from("-1 is not a Unicode code point")),
+            ),
+            (
+                "ord",
+                vec![Object::String("ab".into())],
+                Err(String::from(
+                    "wrong arguments to `ord`: got (STRING), want (CHAR)",
+                )),
+            ),
+            (
+                "chr",
+                vec![],
+                Err(String::from(
+                    "wrong arguments to `chr`: got (), want (INTEGER)",
+                )),
+            ),
+        ];
+
+        for (name, args, expected) in tests {
+            let result = call(name, &args);
+            println!(
+                "{}({:?}): expected {:?}, got {:?}",
+                name, args, expected, result
+            );
+            if result != expected {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1712 @@
+//! A lossless concrete syntax tree.
+//!
+//! The green tree holds the text of every token, including whitespace and
+//! comments, but not where it sits, so unchanged subtrees can be shared
+//! between versions of a file. `SyntaxNode` and `SyntaxToken` make up the red
+//! tree on top of it, adding parent links and positions. Printing either tree
+//! reproduces the input byte for byte.
+//!
+//! There is a node for every statement and expression the parser reads, and
+//! lowering a node gives back the AST the parser built for it.
+
+use crate::ast::{
+    Alternative, ArrayLiteral, AssignExpression, BlockStatement, Boolean, BreakStatement,
+    CallExpression, CharLiteral, ContinueStatement, Expression, ExpressionStatement, FloatLiteral,
+    ForStatement, FunctionLiteral, HashLiteral, Identifier, IfExpression, IndexExpression,
+    InfixExpression, IntegerLiteral, InterpolatedString, InterpolationPart, LetStatement,
+    PrefixExpression, Program, ReturnStatement, Statement, StringLiteral, WhileStatement,
+};
+use crate::lexer::Lexer;
+use crate::parser::{float_value, integer_value, Parser};
+use crate::span::{Position, Span};
+use crate::tokens::{Token, TokenType};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NodeKind {
+    /// The whole file.
+    Root,
+    /// `let x = ...;`, with the doc comments above it.
+    Let,
+    /// `return ...;`
+    Return,
+    /// An expression read as a statement, with its `;` if it has one.
+    ExpressionStatement,
+    /// `while (...) { ... }`
+    While,
+    /// `for (x in ...) { ... }`, with the variable as a bare token.
+    For,
+    /// `break;`
+    Break,
+    /// `continue;`
+    Continue,
+    /// A statement the parser gave up on.
+    Error,
+    Identifier,
+    /// An integer, float, string, char or boolean literal.
+    Literal,
+    /// A string with `${...}` in it.
+    Interpolation,
+    /// `(...)`, which has no node in the AST.
+    Paren,
+    /// `-x`, `!x`
+    Prefix,
+    /// `a + b`, `a && b`, ...
+    Infix,
+    /// `x += 1`, ...
+    Assign,
+    /// `if (...) { ... }`, with its `else` if it has one. The `if` of an
+    /// `else if` has a node of its own.
+    If,
+    /// `{ ... }`, holding a node for each statement in it.
+    Block,
+    /// `fn(x, y) { ... }`, with the parameters as bare tokens.
+    Function,
+    /// `f(a, b)`, holding the function's node and then each argument's.
+    Call,
+    /// `[a, b]`
+    Array,
+    /// `a[i]`
+    Index,
+    /// `{k: v}`, holding a node for each key and each value, in order.
+    Hash,
+}
+
+impl NodeKind {
+    /// The kind of node that holds `expression`.
+    pub fn of_expression(expression: &Expression) -> NodeKind {
+        match expression {
+            Expression::Identifier(_) => NodeKind::Identifier,
+            Expression::IntegerLiteral(_)
+            | Expression::FloatLiteral(_)
+            | Expression::StringLiteral(_)
+            | Expression::CharLiteral(_)
+            | Expression::Boolean(_) => NodeKind::Literal,
+            Expression::InterpolatedString(_) => NodeKind::Interpolation,
+            Expression::PrefixExpression(_) => NodeKind::Prefix,
+            Expression::InfixExpression(_) => NodeKind::Infix,
+            Expression::AssignExpression(_) => NodeKind::Assign,
+            Expression::IfExpression(_) => NodeKind::If,
+            Expression::FunctionLiteral(_) => NodeKind::Function,
+            Expression::CallExpression(_) => NodeKind::Call,
+            Expression::ArrayLiteral(_) => NodeKind::Array,
+            Expression::IndexExpression(_) => NodeKind::Index,
+            Expression::HashLiteral(_) => NodeKind::Hash,
+        }
+    }
+
+    /// The kind of node that holds what `Parser::parse_statement` returned.
+    pub fn of_statement(statement: Option<&Statement>) -> NodeKind {
+        match statement {
+            Some(Statement::Let(_)) => NodeKind::Let,
+            Some(Statement::Return(_)) => NodeKind::Return,
+            Some(Statement::Expression(_)) => NodeKind::ExpressionStatement,
+            Some(Statement::While(_)) => NodeKind::While,
+            Some(Statement::For(_)) => NodeKind::For,
+            Some(Statement::Break(_)) => NodeKind::Break,
+            Some(Statement::Continue(_)) => NodeKind::Continue,
+            None => NodeKind::Error,
+        }
+    }
+
+    pub fn is_statement(self) -> bool {
+        matches!(
+            self,
+            NodeKind::Let
+                | NodeKind::Return
+                | NodeKind::ExpressionStatement
+                | NodeKind::While
+                | NodeKind::For
+                | NodeKind::Break
+                | NodeKind::Continue
+                | NodeKind::Error
+        )
+    }
+}
+
+/// How much text there is, in enough detail to find the position just past
+/// it from the position it starts at.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct TextSize {
+    /// Length in bytes.
+    pub len: usize,
+    /// Number of line breaks.
+    pub lines: usize,
+    /// Characters after the last line break, or in the whole text if there
+    /// is none.
+    pub columns: usize,
+}
+
+impl TextSize {
+    pub fn of(text: &str) -> TextSize {
+        let last_line = text.rfind('\n').map_or(text, |i| &text[i + 1..]);
+
+        TextSize {
+            len: text.len(),
+            lines: text.matches('\n').count(),
+            columns: last_line.chars().count(),
+        }
+    }
+
+    /// The size of this text followed by `next`.
+    pub fn append(self, next: TextSize) -> TextSize {
+        TextSize {
+            len: self.len + next.len,
+            lines: self.lines + next.lines,
+            columns: if next.lines == 0 {
+                self.columns + next.columns
+            } else {
+                next.columns
+            },
+        }
+    }
+
+    /// The position just past text of this size that starts at `start`.
+    pub fn end(self, start: Position) -> Position {
+        if self.lines == 0 {
+            Position::new(
+                start.offset + self.len,
+                start.line,
+                start.column + self.columns,
+            )
+        } else {
+            Position::new(
+                start.offset + self.len,
+                start.line + self.lines,
+                self.columns + 1,
+            )
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenToken {
+    pub token_type: TokenType,
+    pub text: String,
+    /// The lexer's literal, when it differs from the text, e.g. a string's
+    /// contents with escapes applied.
+    pub literal: Option<String>,
+}
+
+impl GreenToken {
+    pub fn literal(&self) -> &str {
+        self.literal.as_deref().unwrap_or(&self.text)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenNode {
+    pub kind: NodeKind,
+    pub children: Vec<GreenElement>,
+    pub size: TextSize,
+    /// How many more interpolations the lexer is inside after the node's
+    /// text than before it.
+    pub templates: isize,
+    /// Errors the parser reported while reading a statement node.
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenNode {
+    pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> GreenNode {
+        let size = children
+            .iter()
+            .fold(TextSize::default(), |size, child| size.append(child.size()));
+        let templates = children.iter().map(GreenElement::templates).sum();
+
+        GreenNode {
+            kind,
+            children,
+            size,
+            templates,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl GreenElement {
+    pub fn size(&self) -> TextSize {
+        match self {
+            GreenElement::Node(node) => node.size,
+            GreenElement::Token(token) => TextSize::of(&token.text),
+        }
+    }
+
+    /// How many more interpolations the lexer is inside after this element.
+    pub fn templates(&self) -> isize {
+        match self {
+            GreenElement::Node(node) => node.templates,
+            GreenElement::Token(token) => match token.token_type {
+                TokenType::TemplateHead => 1,
+                TokenType::TemplateTail => -1,
+                // The rest of a string left unclosed after an interpolation.
+                TokenType::Illegal if token.text.starts_with('}') => -1,
+                _ => 0,
+            },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.size.len,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            write!(f, "{}", child)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for GreenElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GreenElement::Node(node) => write!(f, "{}", node),
+            GreenElement::Token(token) => write!(f, "{}", token.text),
+        }
+    }
+}
+
+/// A green node along with its parent and where it starts in the file.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    start: Position,
+}
+
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    start: Position,
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> SyntaxNode {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: None,
+            start: Position::default(),
+        }))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// This node, its parent, its parent's parent and so on up to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    pub fn start(&self) -> Position {
+        self.0.start
+    }
+
+    pub fn end(&self) -> Position {
+        self.0.green.size.end(self.0.start)
+    }
+
+    /// The bytes of the file this node covers.
+    pub fn range(&self) -> Range<usize> {
+        self.0.start.offset..self.0.start.offset + self.0.green.size.len
+    }
+
+    pub fn children(&self) -> Vec<SyntaxElement> {
+        let mut start = self.0.start;
+
+        self.0
+            .green
+            .children
+            .iter()
+            .map(|child| {
+                let element = match child {
+                    GreenElement::Node(green) => {
+                        SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                            green: green.clone(),
+                            parent: Some(self.clone()),
+                            start,
+                        })))
+                    }
+                    GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                        green: green.clone(),
+                        parent: self.clone(),
+                        start,
+                    }),
+                };
+                start = child.size().end(start);
+                element
+            })
+            .collect()
+    }
+
+    pub fn child_nodes(&self) -> Vec<SyntaxNode> {
+        self.children()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    /// The tokens directly under this node, leaving out whitespace and
+    /// comments.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        self.children()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Token(token) if !is_trivia(token.token_type()) => Some(token),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The first token anywhere under this node that isn't whitespace or a
+    /// comment.
+    pub fn first_token(&self) -> Option<SyntaxToken> {
+        self.children().into_iter().find_map(|child| match child {
+            SyntaxElement::Node(node) => node.first_token(),
+            SyntaxElement::Token(token) if !is_trivia(token.token_type()) => Some(token),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The token that covers byte `offset` of the file, if this node does.
+    pub fn token_at(&self, offset: usize) -> Option<SyntaxToken> {
+        self.children().into_iter().find_map(|child| match child {
+            SyntaxElement::Node(node) if node.range().contains(&offset) => node.token_at(offset),
+            SyntaxElement::Token(token) if token.range().contains(&offset) => Some(token),
+            _ => None,
+        })
+    }
+
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+}
+
+impl SyntaxToken {
+    pub fn token_type(&self) -> &TokenType {
+        &self.green.token_type
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn literal(&self) -> &str {
+        self.green.literal()
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset..self.start.offset + self.green.text.len()
+    }
+
+    /// The token as the lexer handed it out.
+    pub fn token(&self) -> Token<'static> {
+        Token {
+            token_type: self.green.token_type.clone(),
+            literal: Cow::Owned(self.literal().to_string()),
+            span: Span::new(None, self.start, TextSize::of(self.text()).end(self.start)),
+        }
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.green)
+    }
+}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.kind(), self.range())
+    }
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.green.text)
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}@{:?} {:?}",
+            self.token_type(),
+            self.range(),
+            self.text()
+        )
+    }
+}
+
+impl fmt::Display for SyntaxElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxElement::Node(node) => write!(f, "{}", node),
+            SyntaxElement::Token(token) => write!(f, "{}", token),
+        }
+    }
+}
+
+/// Builds the syntax tree for `input`.
+pub fn parse(input: &str) -> SyntaxNode {
+    SyntaxNode::new_root(Rc::new(GreenNode::new(NodeKind::Root, statements(input))))
+}
+
+/// Lowers the tree under `root` into the AST, returning it along with the
+/// parser's errors. The result is the same as parsing the text directly.
+pub fn lower(root: &SyntaxNode) -> (Program, Vec<String>) {
+    let mut program = Program {
+        statements: Vec::new(),
+    };
+    let mut errors = Vec::new();
+
+    for node in root.child_nodes() {
+        program.statements.extend(lower_statement(&node));
+        errors.extend(node.green().errors.iter().cloned());
+    }
+
+    (program, errors)
+}
+
+/// The statement the parser read into `node`, with spans taken from where
+/// the node is. An `Error` node has none.
+pub fn lower_statement(node: &SyntaxNode) -> Option<Statement> {
+    let tokens = node.tokens();
+    let mut nodes = node.child_nodes().into_iter();
+
+    let statement = match node.kind() {
+        NodeKind::Let => {
+            let docs: Vec<_> = node
+                .children()
+                .into_iter()
+                .filter_map(|child| match child {
+                    SyntaxElement::Token(token) if token.token_type() == &TokenType::DocComment => {
+                        Some(token.literal().to_string())
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            Statement::Let(LetStatement {
+                token: tokens.first()?.token(),
+                doc: (!docs.is_empty()).then(|| docs.join("\n")),
+                name: identifier(tokens.get(1)?),
+                value: nodes.next().and_then(|node| lower_expression(&node)),
+            })
+        }
+        NodeKind::Return => Statement::Return(ReturnStatement {
+            token: tokens.first()?.token(),
+            return_value: nodes.next().and_then(|node| lower_expression(&node)),
+        }),
+        NodeKind::ExpressionStatement => Statement::Expression(ExpressionStatement {
+            token: node.first_token()?.token(),
+            expression: nodes.next().and_then(|node| lower_expression(&node)),
+        }),
+        NodeKind::While => Statement::While(WhileStatement {
+            token: tokens.first()?.token(),
+            condition: lower_expression(&nodes.next()?)?,
+            body: lower_block(&nodes.next()?)?,
+        }),
+        NodeKind::For => Statement::For(ForStatement {
+            token: tokens.first()?.token(),
+            variable: identifier(
+                tokens
+                    .iter()
+                    .find(|token| token.token_type() == &TokenType::Ident)?,
+            ),
+            iterable: lower_expression(&nodes.next()?)?,
+            body: lower_block(&nodes.next()?)?,
+        }),
+        NodeKind::Break => Statement::Break(BreakStatement {
+            token: tokens.first()?.token(),
+        }),
+        NodeKind::Continue => Statement::Continue(ContinueStatement {
+            token: tokens.first()?.token(),
+        }),
+        _ => return None,
+    };
+
+    Some(statement)
+}
+
+/// The expression the parser read into `node`, with spans taken from where
+/// the node is.
+pub fn lower_expression(node: &SyntaxNode) -> Option<Expression> {
+    let tokens = node.tokens();
+    let mut nodes = node.child_nodes().into_iter();
+
+    let expression = match node.kind() {
+        NodeKind::Identifier => Expression::Identifier(identifier(tokens.first()?)),
+        NodeKind::Literal => {
+            let token = tokens.first()?;
+            let literal = token.literal();
+
+            match token.token_type() {
+                TokenType::Int => Expression::IntegerLiteral(IntegerLiteral {
+                    token: token.token(),
+                    value: integer_value(literal).ok()?,
+                }),
+                TokenType::Float => Expression::FloatLiteral(FloatLiteral {
+                    token: token.token(),
+                    value: float_value(literal).ok()?,
+                }),
+                TokenType::String => Expression::StringLiteral(StringLiteral {
+                    token: token.token(),
+                    value: literal.to_string(),
+                }),
+                TokenType::Char => Expression::CharLiteral(CharLiteral {
+                    token: token.token(),
+                    value: literal.chars().next()?,
+                }),
+                TokenType::True | TokenType::False => Expression::Boolean(Boolean {
+                    token: token.token(),
+                    value: token.token_type() == &TokenType::True,
+                }),
+                _ => return None,
+            }
+        }
+        NodeKind::Interpolation => {
+            let mut parts = Vec::new();
+
+            for child in node.children() {
+                match child {
+                    SyntaxElement::Node(node) => {
+                        parts.push(InterpolationPart::Expression(lower_expression(&node)?))
+                    }
+                    SyntaxElement::Token(token) if !is_trivia(token.token_type()) => {
+                        if !token.literal().is_empty() {
+                            parts.push(InterpolationPart::Literal(token.literal().to_string()));
+                        }
+                    }
+                    SyntaxElement::Token(_) => {}
+                }
+            }
+
+            Expression::InterpolatedString(InterpolatedString {
+                token: tokens.first()?.token(),
+                parts,
+            })
+        }
+        NodeKind::Paren => return lower_expression(&nodes.next()?),
+        NodeKind::If => Expression::IfExpression(Box::new(lower_if(node)?)),
+        NodeKind::Prefix => {
+            let token = tokens.first()?;
+
+            Expression::PrefixExpression(PrefixExpression {
+                token: token.token(),
+                operator: token.literal().to_string(),
+                right: Box::new(lower_expression(&nodes.next()?)?),
+            })
+        }
+        NodeKind::Infix => {
+            let left = lower_expression(&nodes.next()?)?;
+            let token = tokens.first()?;
+
+            Expression::InfixExpression(InfixExpression {
+                token: token.token(),
+                left: Box::new(left),
+                operator: token.literal().to_string(),
+                right: Box::new(lower_expression(&nodes.next()?)?),
+            })
+        }
+        NodeKind::Assign => {
+            let target = lower_expression(&nodes.next()?)?;
+            let token = tokens.first()?;
+
+            Expression::AssignExpression(AssignExpression {
+                token: token.token(),
+                target: Box::new(target),
+                operator: token.literal().to_string(),
+                value: Box::new(lower_expression(&nodes.next()?)?),
+            })
+        }
+        NodeKind::Function => Expression::FunctionLiteral(Box::new(FunctionLiteral {
+            token: tokens.first()?.token(),
+            parameters: tokens
+                .iter()
+                .filter(|token| token.token_type() == &TokenType::Ident)
+                .map(identifier)
+                .collect(),
+            body: lower_block(&nodes.next()?)?,
+        })),
+        NodeKind::Call => {
+            let function = lower_expression(&nodes.next()?)?;
+
+            Expression::CallExpression(CallExpression {
+                token: tokens.first()?.token(),
+                function: Box::new(function),
+                arguments: nodes
+                    .map(|node| lower_expression(&node))
+                    .collect::<Option<_>>()?,
+            })
+        }
+        NodeKind::Array => Expression::ArrayLiteral(ArrayLiteral {
+            token: tokens.first()?.token(),
+            elements: nodes
+                .map(|node| lower_expression(&node))
+                .collect::<Option<_>>()?,
+        }),
+        NodeKind::Index => {
+            let left = lower_expression(&nodes.next()?)?;
+
+            Expression::IndexExpression(IndexExpression {
+                token: tokens.first()?.token(),
+                left: Box::new(left),
+                index: Box::new(lower_expression(&nodes.next()?)?),
+            })
+        }
+        NodeKind::Hash => {
+            let mut pairs = Vec::new();
+
+            while let Some(key) = nodes.next() {
+                pairs.push((lower_expression(&key)?, lower_expression(&nodes.next()?)?));
+            }
+
+            Expression::HashLiteral(HashLiteral {
+                token: tokens.first()?.token(),
+                pairs,
+            })
+        }
+        _ => return None,
+    };
+
+    Some(expression)
+}
+
+fn lower_if(node: &SyntaxNode) -> Option<IfExpression> {
+    let mut nodes = node.child_nodes().into_iter();
+    let condition = lower_expression(&nodes.next()?)?;
+    let consequence = lower_block(&nodes.next()?)?;
+
+    let alternative = match nodes.next() {
+        Some(node) if node.kind() == NodeKind::If => {
+            Some(Alternative::If(Box::new(lower_if(&node)?)))
+        }
+        Some(node) => Some(Alternative::Block(lower_block(&node)?)),
+        None => None,
+    };
+
+    Some(IfExpression {
+        token: node.tokens().first()?.token(),
+        condition: Box::new(condition),
+        consequence,
+        alternative,
+    })
+}
+
+fn lower_block(node: &SyntaxNode) -> Option<BlockStatement> {
+    Some(BlockStatement {
+        token: node.tokens().first()?.token(),
+        statements: node
+            .child_nodes()
+            .iter()
+            .filter_map(lower_statement)
+            .collect(),
+    })
+}
+
+fn identifier(token: &SyntaxToken) -> Identifier {
+    Identifier {
+        token: token.token(),
+        value: token.literal().to_string(),
+    }
+}
+
+/// The node in the tree under `root` that `statement` was read from, or
+/// lowered from.
+pub fn statement_node(root: &SyntaxNode, statement: &Statement) -> Option<SyntaxNode> {
+    find_node(
+        root,
+        NodeKind::of_statement(Some(statement)),
+        statement.token(),
+    )
+}
+
+/// The node in the tree under `root` that `expression` was read from, or
+/// lowered from.
+pub fn expression_node(root: &SyntaxNode, expression: &Expression) -> Option<SyntaxNode> {
+    find_node(
+        root,
+        NodeKind::of_expression(expression),
+        expression.token(),
+    )
+}
+
+/// The innermost node of `kind` around `token`.
+fn find_node(root: &SyntaxNode, kind: NodeKind, token: &Token) -> Option<SyntaxNode> {
+    root.token_at(token.span.start.offset)?
+        .parent()
+        .ancestors()
+        .find(|node| node.kind() == kind)
+}
+
+/// Splits `input` into statement nodes, leaving the whitespace and comments
+/// between statements as bare tokens.
+pub fn statements(input: &str) -> Vec<GreenElement> {
+    Children::new(input, Position::default()).collect()
+}
+
+/// Replaces the bytes in `range` with `replacement`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, replacement: &str) -> TextEdit {
+        TextEdit {
+            range,
+            replacement: replacement.to_string(),
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        text.replace_range(self.range.clone(), &self.replacement);
+        text
+    }
+}
+
+/// Builds the tree for `root`'s text with `edit` applied, reusing the nodes
+/// of statements the edit cannot have changed.
+///
+/// Lexing and parsing restart at a statement that ends before the edit, far
+/// enough that the lexer never looked ahead into it, since where the
+/// statement ends depends on the token after it. They stop at the first
+/// statement past the edit that starts where one did before, and the old
+/// nodes are used from there on. Only the text of the old nodes in between
+/// is put together to be read again.
+///
+/// A statement can end inside an interpolation, when it is an unfinished
+/// `"${`, so only statements starting outside of any count as places to
+/// restart or stop.
+pub fn reparse(root: &SyntaxNode, edit: &TextEdit) -> SyntaxNode {
+    let mut tree = reparse_edit(root, edit);
+
+    // Errors are messages with positions written into them, so a statement
+    // node with errors is read again if the edit moved it.
+    let old: HashMap<*const GreenNode, Position> = root
+        .child_nodes()
+        .iter()
+        .filter(|node| !node.green().errors.is_empty())
+        .map(|node| (Rc::as_ptr(node.green()), node.start()))
+        .collect();
+
+    while let Some(node) = tree.child_nodes().into_iter().find(|node| {
+        old.get(&Rc::as_ptr(node.green()))
+            .is_some_and(|&start| start != node.start())
+    }) {
+        let edit = TextEdit::new(node.range(), &node.text());
+        tree = reparse_edit(&tree, &edit);
+    }
+
+    tree
+}
+
+fn reparse_edit(root: &SyntaxNode, edit: &TextEdit) -> SyntaxNode {
+    let old = &root.green().children;
+
+    // Where each old child starts, and how many interpolations are open
+    // there, with where the last one ends at the end.
+    let mut starts = Vec::with_capacity(old.len() + 1);
+    let mut templates = Vec::with_capacity(old.len());
+    let (mut position, mut depth) = (root.start(), 0);
+    for child in old {
+        starts.push(position);
+        templates.push(depth);
+        position = child.size().end(position);
+        depth += child.templates();
+    }
+    starts.push(position);
+
+    let edited = starts[1..].partition_point(|start| start.offset <= edit.range.start);
+    let restart = (0..edited)
+        .rev()
+        .find(|&i| {
+            is_statement(&old[i])
+                && templates[i] == 0
+                && lookahead_end(&old[i + 1..], starts[i + 1].offset) <= edit.range.start
+        })
+        .unwrap_or(0);
+
+    // Read the old children from `restart` up to a few statements past the
+    // edit, and twice as many each time that isn't enough to line up again.
+    let past_edit = starts[..old.len()].partition_point(|start| start.offset < edit.range.end);
+    let mut extra = 2;
+
+    loop {
+        let stop = (past_edit + extra).min(old.len());
+        if let Some(children) = reparse_window(old, &starts, &templates, restart..stop, edit) {
+            return SyntaxNode::new_root(Rc::new(GreenNode::new(NodeKind::Root, children)));
+        }
+        extra *= 2;
+    }
+}
+
+/// Reads the old children in `window` again with `edit` applied, and
+/// returns the new children of the root: the old ones before the window,
+/// the new ones read, and the old ones from the first statement that lines
+/// up again. None if no statement in the window does, unless the window
+/// reaches the end of the file.
+fn reparse_window(
+    old: &[GreenElement],
+    starts: &[Position],
+    templates: &[isize],
+    window: Range<usize>,
+    edit: &TextEdit,
+) -> Option<Vec<GreenElement>> {
+    let start = starts[window.start];
+    let mut text: String = old[window.clone()]
+        .iter()
+        .map(GreenElement::to_string)
+        .collect();
+    text.replace_range(
+        edit.range.start - start.offset..edit.range.end - start.offset,
+        &edit.replacement,
+    );
+
+    let at_end = window.end == old.len();
+    let window_end = starts[window.end].offset;
+    let inserted_end = edit.range.start + edit.replacement.len();
+
+    let mut children = old[..window.start].to_vec();
+    let (mut offset, mut depth) = (start.offset, 0);
+
+    for child in Children::new(&text, start) {
+        if offset >= inserted_end && depth == 0 && is_statement(&child) {
+            let old_offset = offset - inserted_end + edit.range.end;
+
+            // The statement before this one was read up to this one's first
+            // token, so that has to have been lexed from the whole of it.
+            let found = starts[..old.len()].binary_search_by_key(&old_offset, |start| start.offset);
+            if let Ok(i) = found {
+                if is_statement(&old[i])
+                    && templates[i] == 0
+                    && (at_end || lookahead_end(&old[i + 1..], starts[i + 1].offset) <= window_end)
+                {
+                    children.extend_from_slice(&old[i..]);
+                    return Some(children);
+                }
+            }
+        }
+
+        offset += child.len();
+        depth += child.templates();
+        children.push(child);
+    }
+
+    at_end.then_some(children)
+}
+
+/// The children of a root node, built one at a time, so nothing past the
+/// last one asked for is lexed or parsed.
+struct Children<'src> {
+    /// The text being read, which starts at `start` in the whole file.
+    text: &'src str,
+    start: Position,
+    parser: Parser<'src>,
+    /// Lexes the same text with trivia, for the tokens that go in the tree.
+    lexer: Lexer<'src>,
+    /// Tokens lexed but not yet handed out.
+    pending: VecDeque<Token<'src>>,
+    /// The statement the parser last read, until its node is handed out.
+    statement: Option<ParsedStatement>,
+}
+
+struct ParsedStatement {
+    kind: NodeKind,
+    /// From the start of its first token to the end of its last.
+    range: Range<usize>,
+    nodes: Vec<(NodeKind, Range<usize>)>,
+    errors: Vec<String>,
+}
+
+impl<'src> Children<'src> {
+    /// Reads `text`, which starts at `start` in the whole file. That must be
+    /// the start of a statement, or of the whitespace and comments before
+    /// one, outside of any interpolation.
+    fn new(text: &'src str, start: Position) -> Self {
+        let mut lexer = Lexer::resume(text, start);
+        lexer.trivia = true;
+
+        let mut parser = Parser::new(Lexer::resume(text, start));
+        parser.nodes = Some(Vec::new());
+
+        Children {
+            text,
+            start,
+            parser,
+            lexer,
+            pending: VecDeque::new(),
+            statement: None,
+        }
+    }
+
+    fn next_token(&mut self) -> Option<GreenElement> {
+        let token = match self.pending.pop_front() {
+            Some(token) => token,
+            None => self.lexer.next()?,
+        };
+
+        Some(green_token(&token, self.text, self.start.offset))
+    }
+
+    fn parse_statement(&mut self) -> ParsedStatement {
+        let start = self.parser.cur_token.span.start.offset;
+        let statement = self.parser.parse_statement();
+        let end = self.parser.cur_token.span.end.offset;
+
+        let mut nodes = self.parser.nodes.replace(Vec::new()).unwrap_or_default();
+        if statement.is_none() {
+            nodes.clear();
+        }
+        let errors = std::mem::take(&mut self.parser.errors);
+        self.parser.next_token();
+
+        ParsedStatement {
+            kind: NodeKind::of_statement(statement.as_ref()),
+            range: start..end,
+            nodes,
+            errors,
+        }
+    }
+}
+
+impl<'src> Iterator for Children<'src> {
+    type Item = GreenElement;
+
+    fn next(&mut self) -> Option<GreenElement> {
+        if self.statement.is_none() && self.parser.cur_token.token_type != TokenType::Eof {
+            self.statement = Some(self.parse_statement());
+        }
+
+        let Some(statement) = &self.statement else {
+            return self.next_token();
+        };
+        let Range { start, end } = statement.range;
+
+        while self
+            .pending
+            .back()
+            .is_none_or(|token| token.span.start.offset < end)
+        {
+            match self.lexer.next() {
+                Some(token) => self.pending.push_back(token),
+                None => break,
+            }
+        }
+
+        let mut first = self
+            .pending
+            .partition_point(|token| token.span.start.offset < start);
+
+        // Doc comments directly above the statement belong to it.
+        let mut i = first;
+        while i > 0 && is_trivia(&self.pending[i - 1].token_type) {
+            i -= 1;
+            if self.pending[i].token_type == TokenType::DocComment {
+                first = i;
+            }
+        }
+
+        if first > 0 {
+            return self.next_token();
+        }
+
+        let mut last = self
+            .pending
+            .partition_point(|token| token.span.start.offset < end);
+
+        // A statement the parser read to the end of input doesn't take the
+        // whitespace and comments there.
+        while last > 0 && is_trivia(&self.pending[last - 1].token_type) {
+            last -= 1;
+        }
+
+        let tokens: Vec<Token> = self.pending.drain(..last).collect();
+        let statement = self.statement.take()?;
+        let mut node = build_node(
+            statement.kind,
+            &tokens,
+            statement.nodes,
+            self.text,
+            self.start.offset,
+        );
+        node.errors = statement.errors;
+
+        Some(GreenElement::Node(Rc::new(node)))
+    }
+}
+
+/// The AST of a syntax tree, kept statement node by statement node.
+///
+/// After an edit, `update` it with the tree `reparse` returned: statement
+/// nodes kept from the old tree keep their AST, with its spans moved to where
+/// the node now starts, and only the nodes `reparse` rebuilt are lowered.
+#[derive(Default)]
+pub struct Lowered {
+    nodes: Vec<LoweredNode>,
+}
+
+struct LoweredNode {
+    green: Rc<GreenNode>,
+    /// Where the node started when it was lowered.
+    start: Position,
+    statement: Option<Statement>,
+}
+
+impl Lowered {
+    pub fn new(root: &SyntaxNode) -> Lowered {
+        let mut lowered = Lowered::default();
+        lowered.update(root);
+        lowered
+    }
+
+    /// Brings the AST up to date with the tree under `root`, returning how
+    /// many statement nodes were lowered.
+    pub fn update(&mut self, root: &SyntaxNode) -> usize {
+        let mut old: HashMap<*const GreenNode, LoweredNode> = self
+            .nodes
+            .drain(..)
+            .map(|node| (Rc::as_ptr(&node.green), node))
+            .collect();
+        let mut lowered = 0;
+
+        for node in root.child_nodes() {
+            match old.remove(&Rc::as_ptr(node.green())) {
+                Some(mut kept) => {
+                    kept.move_to(node.start());
+                    self.nodes.push(kept);
+                }
+                _ => {
+                    self.nodes.push(LoweredNode {
+                        green: node.green().clone(),
+                        start: node.start(),
+                        statement: lower_statement(&node),
+                    });
+                    lowered += 1;
+                }
+            }
+        }
+
+        lowered
+    }
+
+    pub fn statements(&self) -> impl Iterator<Item = &Statement> {
+        self.nodes.iter().filter_map(|node| node.statement.as_ref())
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &String> {
+        self.nodes.iter().flat_map(|node| &node.green.errors)
+    }
+
+    pub fn into_program(self) -> (Program, Vec<String>) {
+        let mut program = Program {
+            statements: Vec::new(),
+        };
+        let mut errors = Vec::new();
+
+        for node in self.nodes {
+            errors.extend(node.green.errors.iter().cloned());
+            program.statements.extend(node.statement);
+        }
+
+        (program, errors)
+    }
+}
+
+impl LoweredNode {
+    /// Moves the spans in the statement from `self.start` to `start`.
+    fn move_to(&mut self, start: Position) {
+        let from = self.start;
+        let shift = |position: &mut Position| {
+            if position.line == from.line {
+                position.column = position.column - from.column + start.column;
+            }
+            position.line = position.line - from.line + start.line;
+            position.offset = position.offset - from.offset + start.offset;
+        };
+
+        if let Some(statement) = &mut self.statement {
+            if from != start {
+                statement.for_each_token_mut(&mut |token| {
+                    shift(&mut token.span.start);
+                    shift(&mut token.span.end);
+                });
+            }
+        }
+
+        self.start = start;
+    }
+}
+
+/// Builds a node of `kind` from `tokens`, nesting them in the nodes the
+/// parser recorded while reading them. The tokens were lexed from `text`,
+/// which starts at byte `offset` of the whole file.
+fn build_node(
+    kind: NodeKind,
+    tokens: &[Token],
+    mut nodes: Vec<(NodeKind, Range<usize>)>,
+    text: &str,
+    offset: usize,
+) -> GreenNode {
+    // A statement in a block takes the doc comments above it, as one at the
+    // top level does.
+    for (kind, range) in &mut nodes {
+        if kind.is_statement() {
+            let mut i = tokens.partition_point(|t| t.span.start.offset < range.start);
+            while i > 0 && is_trivia(&tokens[i - 1].token_type) {
+                i -= 1;
+                if tokens[i].token_type == TokenType::DocComment {
+                    range.start = tokens[i].span.start.offset;
+                }
+            }
+        }
+    }
+
+    // Outer nodes before the nodes inside them. The parser records a node
+    // after the ones inside it, so of two covering the same bytes the later
+    // one is outside.
+    let mut order: Vec<usize> = (0..nodes.len()).collect();
+    order.sort_by_key(|&i| (nodes[i].1.start, Reverse(nodes[i].1.end), Reverse(i)));
+    let mut order = order.into_iter().peekable();
+
+    // The kind, end and children of each node still open.
+    let mut stack = vec![(kind, usize::MAX, Vec::new())];
+
+    for token in tokens {
+        let start = token.span.start.offset;
+
+        while stack.len() > 1 && stack[stack.len() - 1].1 <= start {
+            close_node(&mut stack);
+        }
+
+        while let Some(i) = order.next_if(|&i| nodes[i].1.start <= start) {
+            stack.push((nodes[i].0, nodes[i].1.end, Vec::new()));
+        }
+
+        stack
+            .last_mut()
+            .unwrap()
+            .2
+            .push(green_token(token, text, offset));
+    }
+
+    while stack.len() > 1 {
+        close_node(&mut stack);
+    }
+
+    let (kind, _, children) = stack.pop().unwrap();
+    GreenNode::new(kind, children)
+}
+
+fn close_node(stack: &mut Vec<(NodeKind, usize, Vec<GreenElement>)>) {
+    let (kind, _, children) = stack.pop().unwrap();
+    let node = GreenElement::Node(Rc::new(GreenNode::new(kind, children)));
+    stack.last_mut().unwrap().2.push(node);
+}
+
+/// Makes a green token of `token`, lexed from `text`, which starts at byte
+/// `offset` of the whole file.
+fn green_token(token: &Token, text: &str, offset: usize) -> GreenElement {
+    let text = &text[token.span.start.offset - offset..token.span.end.offset - offset];
+
+    GreenElement::Token(Rc::new(GreenToken {
+        token_type: token.token_type.clone(),
+        text: text.to_string(),
+        literal: (token.literal != text).then(|| token.literal.to_string()),
+    }))
+}
+
+/// How far past `end` the lexer may have looked to decide where the token
+/// ending there stops, given the elements that follow. That is a few
+/// characters, except that `r` looks past any number of `#`s for the `"` of
+/// a raw string.
+fn lookahead_end(rest: &[GreenElement], end: usize) -> usize {
+    let mut hashes = 0;
+
+    for element in rest {
+        let text = element.to_string();
+        let after = text.trim_start_matches('#');
+        hashes += text.len() - after.len();
+
+        if !after.is_empty() {
+            break;
+        }
+    }
+
+    end + hashes + 4
+}
+
+fn is_statement(element: &GreenElement) -> bool {
+    matches!(element, GreenElement::Node(node) if node.kind != NodeKind::Root)
+}
+
+fn is_trivia(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::Whitespace | TokenType::Comment | TokenType::DocComment
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        expression_node, lower, lower_expression, lower_statement, parse, reparse, statement_node,
+        GreenElement, GreenNode, Lowered, NodeKind, SyntaxElement, SyntaxNode, TextEdit,
+    };
+    use crate::ast::{Alternative, Expression, ExpressionStatement, Node, Statement};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::span::Span;
+    use crate::tokens::TokenType;
+    use std::rc::Rc;
+
+    const INPUTS: [&str; 16] = [
+        "",
+        "let x = 5;",
+        "  let x = 5 ;  // trailing\n\n/* block /* nested */ */ x + 1\n",
+        "/// Adds.\n/// Twice.\nlet add = 1 + 2;\r\n\tadd",
+        "let s = \"tab\\t ${name + \"inner\"} é\";\nlet r = r#\"raw \"quoted\"\"#;",
+        "let c = 'x'; let m = \"\"\"\n    indented\n    \"\"\";",
+        "let = 5; let x 5; @ # \"unterminated",
+        "(a + (b * c)) { x; y } /* unterminated",
+        "let t = !(true == (false)) && ((1 + 2) * 3 > 4);",
+        "if (a) {\n  /// Doc.\n  let b = 1;\n  b\n} else if (!a) { return; } else { if (c) {} }",
+        "x = if (a) { 1 }; if (b) { 2 ",
+        "x += \"${'y' + z}\" || 0x_1F ** -2.5e1 && !a + ;\nreturn",
+        "let f = fn(x, y) {\n  x + y\n};\nf(1, (g)(2))(3); fn() {}(); h(fn(z {}",
+        "let xs = [1, [2, 3],];\nxs[0] + f(xs)[1 + 1]; [1, 2",
+        "let h = {\"a\": {}, b: [1],};\nh[\"a\"]; { x; y } {1: 2, 3",
+        "while (i < 3) {\n  i += 1;\n  if (i == 2) { continue; }\n};\nfor (c in \"ab\") { break } break;",
+    ];
+
+    #[test]
+    fn round_trip() -> Result<(), ()> {
+        for input in INPUTS {
+            let tree = parse(input);
+            println!("input: {:?}, got: {:?}", input, tree.text());
+
+            if tree.text() != input || tree.range() != (0..input.len()) {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn tree_structure() -> Result<(), ()> {
+        let input = "/// Doc.\nlet x = 1;\n// note\nx + 2 * 3;";
+        let tree = parse(input);
+        let statements = tree.child_nodes();
+
+        let texts: Vec<_> = statements.iter().map(|node| node.text()).collect();
+        let kinds: Vec<_> = statements.iter().map(|node| node.kind()).collect();
+        println!("statements: {:?} {:?}", texts, kinds);
+
+        if texts != ["/// Doc.\nlet x = 1;", "x + 2 * 3;"]
+            || kinds != [NodeKind::Let, NodeKind::ExpressionStatement]
+        {
+            return Err(());
+        }
+
+        // The outer infix expression, with offsets into the whole input.
+        let sum = &statements[1].child_nodes()[0];
+        if sum.kind() != NodeKind::Infix
+            || &input[sum.range()] != "x + 2 * 3"
+            || sum.parent().map(|parent| parent.range()) != Some(statements[1].range())
+        {
+            println!("unexpected node: {:?}", sum);
+            return Err(());
+        }
+
+        let children = sum.children();
+        println!("children: {:?}", children);
+
+        match &children[..] {
+            [SyntaxElement::Node(x), SyntaxElement::Token(_), SyntaxElement::Token(plus), SyntaxElement::Token(_), SyntaxElement::Node(product)]
+                if x.kind() == NodeKind::Identifier
+                    && plus.token_type() == &TokenType::Plus
+                    && plus.range() == (30..31)
+                    && product.kind() == NodeKind::Infix
+                    && product.text() == "2 * 3" => {}
+            _ => return Err(()),
+        }
+
+        // Positions count lines and characters, as the lexer does.
+        let start = sum.start();
+        if (start.line, start.column) != (4, 1) {
+            println!("unexpected start: {:?}", start);
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn lowering_matches_parser() -> Result<(), ()> {
+        for input in INPUTS {
+            let mut parser = Parser::new(Lexer::new(input));
+            let expected = parser.parse_program();
+
+            let (program, errors) = lower(&parse(input));
+            println!("input: {:?}\n  got: {:?}", input, errors);
+
+            if program.string() != expected.string() || errors != parser.errors {
+                println!("expected: {:?}", parser.errors);
+                return Err(());
+            }
+
+            if docs(&program.statements) != docs(&expected.statements) {
+                return Err(());
+            }
+
+            let (mut program, mut expected) = (program, expected);
+            let spans = (
+                spans(program.statements.iter_mut()),
+                spans(expected.statements.iter_mut()),
+            );
+            if spans.0 != spans.1 {
+                println!("expected spans: {:?}", spans.1);
+                println!("     got spans: {:?}", spans.0);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ast_nodes_map_to_tree_nodes() -> Result<(), ()> {
+        let input = "let x = 1;\nx += 'a' + \"${x * 2}\" - 3;";
+        let tree = parse(input);
+        let (program, _) = lower(&tree);
+
+        for statement in &program.statements {
+            let Some(node) = statement_node(&tree, statement) else {
+                println!("no node for {}", statement.string());
+                return Err(());
+            };
+
+            let lowered = lower_statement(&node).map(|statement| statement.string());
+            if lowered != Some(statement.string()) {
+                println!("{} lowered to {:?}", statement.string(), lowered);
+                return Err(());
+            }
+        }
+
+        let Some(Statement::Expression(statement)) = program.statements.get(1) else {
+            return Err(());
+        };
+        let Some(assign) = &statement.expression else {
+            return Err(());
+        };
+
+        // From the AST to the node and back again, at every level.
+        let mut expressions = vec![assign];
+        while let Some(expression) = expressions.pop() {
+            let node = expression_node(&tree, expression);
+            let lowered = node.as_ref().and_then(lower_expression);
+            println!("{} -> {:?}", expression.string(), node);
+
+            if lowered.map(|expression| expression.string()) != Some(expression.string()) {
+                return Err(());
+            }
+
+            match expression {
+                crate::ast::Expression::AssignExpression(assign) => {
+                    expressions.extend([assign.target.as_ref(), assign.value.as_ref()])
+                }
+                crate::ast::Expression::InfixExpression(infix) => {
+                    expressions.extend([infix.left.as_ref(), infix.right.as_ref()])
+                }
+                crate::ast::Expression::InterpolatedString(interpolated) => {
+                    for part in &interpolated.parts {
+                        if let crate::ast::InterpolationPart::Expression(expression) = part {
+                            expressions.push(expression);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The spans of every token in `statements`.
+    fn spans<'a>(statements: impl Iterator<Item = &'a mut Statement>) -> Vec<Span> {
+        let mut spans = Vec::new();
+
+        for statement in statements {
+            statement.for_each_token_mut(&mut |token| spans.push(token.span.clone()));
+        }
+
+        spans
+    }
+
+    /// The doc comments of the let statements in `statements`, including
+    /// those in blocks.
+    fn docs(statements: &[Statement]) -> Vec<Option<String>> {
+        let mut found = Vec::new();
+
+        for statement in statements {
+            match statement {
+                Statement::Let(statement) => found.push(statement.doc.clone()),
+                Statement::Expression(ExpressionStatement {
+                    expression: Some(Expression::IfExpression(if_expression)),
+                    ..
+                }) => {
+                    let mut if_expression = Some(if_expression.as_ref());
+                    while let Some(current) = if_expression.take() {
+                        found.extend(docs(&current.consequence.statements));
+                        match &current.alternative {
+                            Some(Alternative::Block(block)) => {
+                                found.extend(docs(&block.statements))
+                            }
+                            Some(Alternative::If(next)) => if_expression = Some(next),
+                            None => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        found
+    }
+
+    fn statement_nodes(root: &SyntaxNode) -> Vec<Rc<GreenNode>> {
+        root.green()
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                GreenElement::Node(node) => Some(node.clone()),
+                GreenElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reparse_matches_full_parse() -> Result<(), ()> {
+        let input = "let a = 1;\n/// Bee.\nlet b = 2;\nx\ny; let c = \"${a}\";\n// end\nlet = 6;\n";
+
+        // Each edit replaces the first occurrence of some text in what the
+        // edits before it left behind.
+        let edits = [
+            ("2;", "20 + x;"),
+            ("let a", "  let a"),
+            ("y;", "* y;"),
+            ("/// Bee.", "// Bee."),
+            ("// Bee.", "/// Bee."),
+            ("; let c", "/*; let c"),
+            ("/*", "\""),
+            ("\"; let c", "; let c"),
+            ("${a}", "${a + \"${b}\"}"),
+            ("  let a = 1;", ""),
+            ("// end", "let d = 4; // end"),
+            ("let b = 20 + x;\nx\n* y;", ""),
+            ("", "let e = 5;\n\n"),
+        ];
+
+        let mut text = input.to_string();
+        let mut tree = parse(&text);
+        let mut lowered = Lowered::new(&tree);
+
+        for (find, replacement) in edits {
+            let start = text.find(find).ok_or(())?;
+            let edit = TextEdit::new(start..start + find.len(), replacement);
+
+            text = edit.apply(&text);
+            let old = statement_nodes(&tree);
+            tree = reparse(&tree, &edit);
+            println!("edit: {:?}\n text: {:?}", edit, text);
+
+            let expected = parse(&text);
+            if tree.green() != expected.green() || tree.text() != text {
+                println!("got: {:?}", tree.text());
+                return Err(());
+            }
+
+            // Only the statement nodes `reparse` rebuilt are lowered again.
+            let rebuilt = statement_nodes(&tree)
+                .iter()
+                .filter(|node| !old.iter().any(|old| Rc::ptr_eq(old, node)))
+                .count();
+            let updated = lowered.update(&tree);
+            println!("lowered: {}, rebuilt: {}", updated, rebuilt);
+            if updated != rebuilt {
+                return Err(());
+            }
+
+            let mut parser = Parser::new(Lexer::new(&text));
+            let mut expected = parser.parse_program();
+            let statements: Vec<String> = lowered.statements().map(Node::string).collect();
+            let errors: Vec<&String> = lowered.errors().collect();
+            if statements
+                != expected
+                    .statements
+                    .iter()
+                    .map(Node::string)
+                    .collect::<Vec<_>>()
+                || errors != parser.errors().iter().collect::<Vec<_>>()
+            {
+                println!("errors: {:?}", errors);
+                return Err(());
+            }
+
+            // Spans of the statements kept from before the edit were moved.
+            let kept = lowered
+                .nodes
+                .iter_mut()
+                .filter_map(|node| node.statement.as_mut());
+            if spans(kept) != spans(expected.statements.iter_mut()) {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reparse_context_sensitive_edits() -> Result<(), ()> {
+        // (text, text to replace, replacement)
+        let tests = [
+            // `r` only starts a raw string if a `"` follows the `#`s.
+            ("x; r##", "##", "##\""),
+            ("x; r### y; z", " y", "\" y\"###"),
+            ("r; 1; 2; 3; 4; 5; #######", "r", "x; r"),
+            // A statement can start inside an interpolation, where `}` goes
+            // back to reading the string.
+            (" ${=}", "$", "\"$"),
+            ("\"${={}}x=1", "1", "1;"),
+        ];
+
+        for (input, find, replacement) in tests {
+            let start = input.rfind(find).ok_or(())?;
+            let edit = TextEdit::new(start..start + find.len(), replacement);
+            let text = edit.apply(input);
+
+            let tree = reparse(&parse(input), &edit);
+            let expected = parse(&text);
+            println!("text: {:?}, got: {:?}", text, tree.children());
+
+            if tree.green() != expected.green() {
+                println!("expected: {:?}", expected.children());
+                return Err(());
+            }
+
+            let (program, errors) = lower(&tree);
+            let mut parser = Parser::new(Lexer::new(&text));
+            if program.string() != parser.parse_program().string() || errors != parser.errors {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reparse_reuses_untouched_statements() -> Result<(), ()> {
+        let text = "let a = 1;\nlet b = 2;\nlet c = 3;\nlet d = 4;\n";
+        let tree = parse(text);
+        let edited = reparse(&tree, &TextEdit::new(30..31, "3 * 10"));
+
+        let before = statement_nodes(&tree);
+        let after = statement_nodes(&edited);
+        println!("after: {:?}", edited.text());
+
+        // The statement before the edit is reparsed, since the edit could
+        // have extended it, but the ones either side of that are kept.
+        let reused: Vec<bool> = before
+            .iter()
+            .zip(&after)
+            .map(|(before, after)| Rc::ptr_eq(before, after))
+            .collect();
+
+        if reused != [true, false, false, true] || after[2].to_string() != "let c = 3 * 10;" {
+            println!("reused: {:?}", reused);
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reparse_rebuilds_moved_errors() -> Result<(), ()> {
+        let text = "let a = 1;\nlet b = 2;\nlet c 3;\nlet d = 4;\n";
+        let tree = parse(text);
+        let edited = reparse(&tree, &TextEdit::new(0..0, "\n"));
+
+        let before = statement_nodes(&tree);
+        let after = statement_nodes(&edited);
+        let errors: Vec<_> = after.iter().flat_map(|node| node.errors.clone()).collect();
+        println!("errors: {:?}", errors);
+
+        // Every statement moved down a line. `let c` failed to parse, leaving
+        // `3;` as a statement of its own; its node is read again for its
+        // error, while the ones after it are kept.
+        let reused: Vec<bool> = before
+            .iter()
+            .zip(&after)
+            .map(|(before, after)| Rc::ptr_eq(before, after))
+            .collect();
+
+        if reused[2..] != [false, true, true]
+            || errors != ["4:7: expected next token to be Assign, got Int instead"]
+        {
+            println!("reused: {:?}", reused);
+            return Err(());
+        }
+
+        Ok(())
+    }
+}
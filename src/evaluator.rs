@@ -0,0 +1,856 @@
+use crate::ast::{
+    Alternative, AssignExpression, BlockStatement, CallExpression, Expression, HashLiteral,
+    IfExpression, IndexExpression, InfixExpression, InterpolatedString, InterpolationPart,
+    PrefixExpression, Program, Statement,
+};
+use crate::builtins;
+use crate::object::{Env, Environment, Function, Object};
+use crate::tokens::Token;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Why evaluation stopped before reaching the end of the program.
+enum Unwind {
+    /// A `return` statement, carrying its value to the top of the program.
+    Return(Object),
+    /// A `break` statement, carrying control to the end of the loop around
+    /// it. The parser only allows one inside a loop, so it never gets past
+    /// one.
+    Break,
+    /// A `continue` statement, carrying control to the next round of the
+    /// loop around it.
+    Continue,
+    /// A runtime error, already prefixed with the location it happened at.
+    Error(String),
+}
+
+type Eval = Result<Object, Unwind>;
+
+fn error(token: &Token, msg: String) -> Unwind {
+    Unwind::Error(format!("{}: {}", token.span, msg))
+}
+
+/// Runs `program` and returns the value of its last statement, or of the
+/// first `return` reached.
+pub fn eval_program(program: &Program, env: &Env) -> Result<Object, String> {
+    let mut result = Object::Null;
+
+    for statement in &program.statements {
+        match eval_statement(statement, env) {
+            Ok(value) => result = value,
+            Err(Unwind::Return(value)) => return Ok(value),
+            Err(Unwind::Error(msg)) => return Err(msg),
+            Err(Unwind::Break | Unwind::Continue) => {
+                unreachable!("the parser rejects break and continue outside of a loop")
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn eval_statement(statement: &Statement, env: &Env) -> Eval {
+    match statement {
+        Statement::Let(let_statement) => {
+            let value = match &let_statement.value {
+                Some(value) => eval_expression(value, env)?,
+                None => Object::Null,
+            };
+            env.borrow_mut()
+                .set(let_statement.name.value.clone(), value);
+            Ok(Object::Null)
+        }
+        Statement::Return(return_statement) => {
+            let value = match &return_statement.return_value {
+                Some(value) => eval_expression(value, env)?,
+                None => Object::Null,
+            };
+            Err(Unwind::Return(value))
+        }
+        Statement::Expression(expression_statement) => match &expression_statement.expression {
+            Some(expression) => eval_expression(expression, env),
+            None => Ok(Object::Null),
+        },
+        Statement::While(while_statement) => {
+            while eval_expression(&while_statement.condition, env)?.is_truthy() {
+                if !eval_loop_body(&while_statement.body, env)? {
+                    break;
+                }
+            }
+            Ok(Object::Null)
+        }
+        Statement::For(for_statement) => {
+            let items = match eval_expression(&for_statement.iterable, env)? {
+                Object::Array(elements) => elements,
+                Object::String(value) => value.chars().map(Object::Char).collect(),
+                Object::Hash(pairs) => pairs.into_iter().map(|(key, _)| key).collect(),
+                other => {
+                    return Err(error(
+                        for_statement.iterable.token(),
+                        format!("cannot iterate over {}", other.type_name()),
+                    ))
+                }
+            };
+
+            for item in items {
+                env.borrow_mut()
+                    .set(for_statement.variable.value.clone(), item);
+                if !eval_loop_body(&for_statement.body, env)? {
+                    break;
+                }
+            }
+            Ok(Object::Null)
+        }
+        Statement::Break(_) => Err(Unwind::Break),
+        Statement::Continue(_) => Err(Unwind::Continue),
+    }
+}
+
+/// Runs one round of a loop, giving whether the loop should go on.
+fn eval_loop_body(body: &BlockStatement, env: &Env) -> Result<bool, Unwind> {
+    match eval_block_statement(body, env) {
+        Ok(_) | Err(Unwind::Continue) => Ok(true),
+        Err(Unwind::Break) => Ok(false),
+        Err(unwind) => Err(unwind),
+    }
+}
+
+fn eval_expression(expression: &Expression, env: &Env) -> Eval {
+    match expression {
+        Expression::Identifier(identifier) => match env
+            .borrow()
+            .get(&identifier.value)
+            .or_else(|| builtins::lookup(&identifier.value))
+        {
+            Some(value) => Ok(value),
+            None => Err(error(
+                &identifier.token,
+                format!("identifier not found: {}", identifier.value),
+            )),
+        },
+        Expression::IntegerLiteral(integer_literal) => Ok(Object::Integer(integer_literal.value)),
+        Expression::FloatLiteral(float_literal) => Ok(Object::Float(float_literal.value)),
+        Expression::StringLiteral(string_literal) => {
+            Ok(Object::String(string_literal.value.clone()))
+        }
+        Expression::CharLiteral(char_literal) => Ok(Object::Char(char_literal.value)),
+        Expression::Boolean(boolean) => Ok(Object::Boolean(boolean.value)),
+        Expression::InterpolatedString(interpolated_string) => {
+            eval_interpolated_string(interpolated_string, env)
+        }
+        Expression::PrefixExpression(prefix_expression) => {
+            eval_prefix_expression(prefix_expression, env)
+        }
+        Expression::InfixExpression(infix_expression) => {
+            eval_infix_expression(infix_expression, env)
+        }
+        Expression::AssignExpression(assign_expression) => {
+            eval_assign_expression(assign_expression, env)
+        }
+        Expression::IfExpression(if_expression) => eval_if_expression(if_expression, env),
+        Expression::FunctionLiteral(function_literal) => Ok(Object::Function(Rc::new(Function {
+            parameters: function_literal.parameters.clone(),
+            body: function_literal.body.clone(),
+            env: Rc::clone(env),
+        }))),
+        Expression::CallExpression(call_expression) => eval_call_expression(call_expression, env),
+        Expression::ArrayLiteral(array_literal) => Ok(Object::Array(
+            array_literal
+                .elements
+                .iter()
+                .map(|element| eval_expression(element, env))
+                .collect::<Result<_, _>>()?,
+        )),
+        Expression::IndexExpression(index_expression) => {
+            eval_index_expression(index_expression, env)
+        }
+        Expression::HashLiteral(hash_literal) => eval_hash_literal(hash_literal, env),
+    }
+}
+
+/// Builds a hash with its keys in source order. A repeated key keeps its
+/// first place but takes the last value.
+fn eval_hash_literal(hash: &HashLiteral, env: &Env) -> Eval {
+    let mut pairs: Vec<(Object, Object)> = Vec::new();
+
+    for (key_expression, value_expression) in &hash.pairs {
+        let key = eval_expression(key_expression, env)?;
+        if !key.is_hashable() {
+            return Err(error(
+                key_expression.token(),
+                format!("unusable as hash key: {}", key.type_name()),
+            ));
+        }
+
+        let value = eval_expression(value_expression, env)?;
+        match pairs.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, existing)) => *existing = value,
+            None => pairs.push((key, value)),
+        }
+    }
+
+    Ok(Object::Hash(pairs))
+}
+
+fn eval_interpolated_string(interpolated: &InterpolatedString, env: &Env) -> Eval {
+    let mut out = String::new();
+
+    for part in &interpolated.parts {
+        match part {
+            InterpolationPart::Literal(text) => out.push_str(text),
+            InterpolationPart::Expression(expression) => {
+                out.push_str(&eval_expression(expression, env)?.to_string())
+            }
+        }
+    }
+
+    Ok(Object::String(out))
+}
+
+fn eval_if_expression(if_expression: &IfExpression, env: &Env) -> Eval {
+    if eval_expression(&if_expression.condition, env)?.is_truthy() {
+        return eval_block_statement(&if_expression.consequence, env);
+    }
+
+    match &if_expression.alternative {
+        Some(Alternative::Block(block)) => eval_block_statement(block, env),
+        Some(Alternative::If(if_expression)) => eval_if_expression(if_expression, env),
+        None => Ok(Object::Null),
+    }
+}
+
+/// Evaluates the statements in order, giving the value of the last one.
+fn eval_block_statement(block: &BlockStatement, env: &Env) -> Eval {
+    let mut result = Object::Null;
+
+    for statement in &block.statements {
+        result = eval_statement(statement, env)?;
+    }
+
+    Ok(result)
+}
+
+fn eval_prefix_expression(prefix: &PrefixExpression, env: &Env) -> Eval {
+    let right = eval_expression(&prefix.right, env)?;
+
+    match (prefix.operator.as_str(), right) {
+        ("!", right) => Ok(Object::Boolean(!right.is_truthy())),
+        ("-", Object::Integer(value)) => value
+            .checked_neg()
+            .map(Object::Integer)
+            .ok_or_else(|| error(&prefix.token, "integer overflow".into())),
+        ("-", Object::Float(value)) => Ok(Object::Float(-value)),
+        (operator, right) => Err(error(
+            &prefix.token,
+            format!("unknown operator: {}{}", operator, right.type_name()),
+        )),
+    }
+}
+
+fn eval_infix_expression(infix: &InfixExpression, env: &Env) -> Eval {
+    let left = eval_expression(&infix.left, env)?;
+
+    // `&&` and `||` only evaluate their right side when the left side
+    // doesn't already decide the result.
+    match infix.operator.as_str() {
+        "&&" if !left.is_truthy() => return Ok(Object::Boolean(false)),
+        "||" if left.is_truthy() => return Ok(Object::Boolean(true)),
+        "&&" | "||" => {
+            let right = eval_expression(&infix.right, env)?;
+            return Ok(Object::Boolean(right.is_truthy()));
+        }
+        _ => {}
+    }
+
+    let right = eval_expression(&infix.right, env)?;
+    eval_binary(&infix.token, &infix.operator, left, right)
+}
+
+fn eval_assign_expression(assign: &AssignExpression, env: &Env) -> Eval {
+    let name = match assign.target.as_ref() {
+        Expression::Identifier(identifier) => identifier.value.clone(),
+        _ => {
+            return Err(error(
+                &assign.token,
+                format!("cannot assign with {}", assign.operator),
+            ))
+        }
+    };
+
+    let current = eval_expression(&assign.target, env)?;
+    let value = eval_expression(&assign.value, env)?;
+
+    // `x += 1` is `x = x + 1`, so drop the `=` to get the operator.
+    let operator = assign.operator.trim_end_matches('=');
+    let value = eval_binary(&assign.token, operator, current, value)?;
+
+    env.borrow_mut().assign(name, value.clone());
+    Ok(value)
+}
+
+fn eval_call_expression(call: &CallExpression, env: &Env) -> Eval {
+    let function = eval_expression(&call.function, env)?;
+    let arguments = call
+        .arguments
+        .iter()
+        .map(|argument| eval_expression(argument, env))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match function {
+        Object::Function(function) => {
+            if arguments.len() != function.parameters.len() {
+                return Err(error(
+                    &call.token,
+                    format!(
+                        "wrong number of arguments: got {}, want {}",
+                        arguments.len(),
+                        function.parameters.len()
+                    ),
+                ));
+            }
+
+            let mut inner = Environment::enclosed(Rc::clone(&function.env));
+            for (parameter, argument) in function.parameters.iter().zip(arguments) {
+                inner.set(parameter.value.clone(), argument);
+            }
+
+            // A `return` stops at the function it is in.
+            match eval_block_statement(&function.body, &Rc::new(RefCell::new(inner))) {
+                Err(Unwind::Return(value)) => Ok(value),
+                result => result,
+            }
+        }
+        Object::Builtin(builtin) => {
+            (builtin.func)(&arguments).map_err(|msg| error(&call.token, msg))
+        }
+        other => Err(error(
+            &call.token,
+            format!("not a function: {}", other.type_name()),
+        )),
+    }
+}
+
+/// Indexes an array, a string by char, or a hash by key. An index past
+/// either end, or a key that isn't there, gives null.
+fn eval_index_expression(index: &IndexExpression, env: &Env) -> Eval {
+    let left = eval_expression(&index.left, env)?;
+    let position = eval_expression(&index.index, env)?;
+
+    match (left, position) {
+        (Object::Array(elements), Object::Integer(i)) => Ok(usize::try_from(i)
+            .ok()
+            .and_then(|i| elements.get(i).cloned())
+            .unwrap_or(Object::Null)),
+        (Object::String(value), Object::Integer(i)) => Ok(usize::try_from(i)
+            .ok()
+            .and_then(|i| value.chars().nth(i))
+            .map_or(Object::Null, Object::Char)),
+        (Object::Hash(pairs), key) if key.is_hashable() => Ok(pairs
+            .into_iter()
+            .find(|(existing, _)| *existing == key)
+            .map_or(Object::Null, |(_, value)| value)),
+        (left, position) => Err(error(
+            &index.token,
+            format!(
+                "index operator not supported: {}[{}]",
+                left.type_name(),
+                position.type_name()
+            ),
+        )),
+    }
+}
+
+fn eval_binary(token: &Token, operator: &str, left: Object, right: Object) -> Eval {
+    match (left, right) {
+        (Object::Integer(left), Object::Integer(right)) => {
+            eval_integer_binary(token, operator, left, right)
+        }
+        (Object::Integer(left), Object::Float(right)) => {
+            eval_float_binary(token, operator, left as f64, right)
+        }
+        (Object::Float(left), Object::Integer(right)) => {
+            eval_float_binary(token, operator, left, right as f64)
+        }
+        (Object::Float(left), Object::Float(right)) => {
+            eval_float_binary(token, operator, left, right)
+        }
+        // Strings and chars join up into a string.
+        (
+            left @ (Object::String(_) | Object::Char(_)),
+            right @ (Object::String(_) | Object::Char(_)),
+        ) if operator == "+" => Ok(Object::String(format!("{}{}", left, right))),
+        (Object::Char(left), Object::Char(right)) => {
+            eval_comparison(token, operator, "CHAR", left, right)
+        }
+        (left, right) if left.type_name() != right.type_name() => Err(error(
+            token,
+            format!(
+                "type mismatch: {} {} {}",
+                left.type_name(),
+                operator,
+                right.type_name()
+            ),
+        )),
+        (left, right) => match operator {
+            "==" => Ok(Object::Boolean(left == right)),
+            "!=" => Ok(Object::Boolean(left != right)),
+            _ => Err(error(
+                token,
+                format!(
+                    "unknown operator: {} {} {}",
+                    left.type_name(),
+                    operator,
+                    right.type_name()
+                ),
+            )),
+        },
+    }
+}
+
+fn eval_integer_binary(token: &Token, operator: &str, left: i64, right: i64) -> Eval {
+    let value = match operator {
+        "+" => left.checked_add(right),
+        "-" => left.checked_sub(right),
+        "*" => left.checked_mul(right),
+        "/" | "%" if right == 0 => return Err(error(token, "division by zero".into())),
+        "/" => left.checked_div(right),
+        "%" => left.checked_rem(right),
+        // A negative power of an integer is a fraction.
+        "**" if right < 0 => return eval_float_binary(token, operator, left as f64, right as f64),
+        "**" => u32::try_from(right)
+            .ok()
+            .and_then(|right| left.checked_pow(right)),
+        _ => return eval_comparison(token, operator, "INTEGER", left, right),
+    };
+
+    value
+        .map(Object::Integer)
+        .ok_or_else(|| error(token, "integer overflow".into()))
+}
+
+fn eval_float_binary(token: &Token, operator: &str, left: f64, right: f64) -> Eval {
+    let value = match operator {
+        "+" => left + right,
+        "-" => left - right,
+        "*" => left * right,
+        "/" => left / right,
+        "%" => left % right,
+        "**" => left.powf(right),
+        _ => return eval_comparison(token, operator, "FLOAT", left, right),
+    };
+
+    Ok(Object::Float(value))
+}
+
+fn eval_comparison<T: PartialOrd>(
+    token: &Token,
+    operator: &str,
+    type_name: &str,
+    left: T,
+    right: T,
+) -> Eval {
+    let value = match operator {
+        "<" => left < right,
+        "<=" => left <= right,
+        ">" => left > right,
+        ">=" => left >= right,
+        "==" => left == right,
+        "!=" => left != right,
+        _ => {
+            return Err(error(
+                token,
+                format!("unknown operator: {0} {1} {0}", type_name, operator),
+            ))
+        }
+    };
+
+    Ok(Object::Boolean(value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::eval_program;
+    use crate::lexer::Lexer;
+    use crate::object::{Env, Environment, Object};
+    use crate::parser::Parser;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn new_env() -> Env {
+        Rc::new(RefCell::new(Environment::new()))
+    }
+
+    fn eval_with(input: &str, env: &Env) -> Result<Object, String> {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+
+        if let Some(msg) = parser.errors().first() {
+            return Err(format!("parser error: {}", msg));
+        }
+
+        eval_program(&program, env)
+    }
+
+    fn eval(input: &str) -> Result<Object, String> {
+        eval_with(input, &new_env())
+    }
+
+    #[test]
+    fn test_arithmetic() -> Result<(), ()> {
+        let tests = vec![
+            ("5", Object::Integer(5)),
+            ("1 + 2 * 3", Object::Integer(7)),
+            ("7 / 2", Object::Integer(3)),
+            ("7 % 3", Object::Integer(1)),
+            ("2 ** 3 ** 2", Object::Integer(512)),
+            ("2 ** 0 - 1", Object::Integer(0)),
+            ("-2 ** 2", Object::Integer(-4)),
+            ("2 ** -1", Object::Float(0.5)),
+            ("7 / 2.0", Object::Float(3.5)),
+            ("1.5 + 1", Object::Float(2.5)),
+            ("2 ** 0.5 * 2 ** 0.5 > 1.99", Object::Boolean(true)),
+            ("1 < 2", Object::Boolean(true)),
+            ("2 <= 1", Object::Boolean(false)),
+            ("3 >= 3.0", Object::Boolean(true)),
+            ("1 + 1 == 2 != 3 < 2", Object::Boolean(true)),
+            ("(1 + 2) * 3", Object::Integer(9)),
+            ("!(true == false)", Object::Boolean(true)),
+            ("(2 < 1) == false", Object::Boolean(true)),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_strings() -> Result<(), ()> {
+        let env = new_env();
+        env.borrow_mut()
+            .set("name".into(), Object::String("world".into()));
+        env.borrow_mut().set("n".into(), Object::Integer(3));
+
+        let tests = vec![
+            (r#""hello""#, Object::String("hello".into())),
+            (r#""a" + "b" + "c""#, Object::String("abc".into())),
+            (r#""a" == "a""#, Object::Boolean(true)),
+            (r#""a" != "a""#, Object::Boolean(false)),
+            (r#""hello ${name}""#, Object::String("hello world".into())),
+            (
+                r#""${n} + 1 = ${n + 1}""#,
+                Object::String("3 + 1 = 4".into()),
+            ),
+            (r#""${"${n * 2}" + "!"}""#, Object::String("6!".into())),
+            (r#"name += "!""#, Object::String("world!".into())),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval_with(input, &env);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_chars() -> Result<(), ()> {
+        let tests = vec![
+            ("'a'", Object::Char('a')),
+            ("'a' < 'b'", Object::Boolean(true)),
+            ("'\\u{e9}' >= 'z'", Object::Boolean(true)),
+            ("'a' == 'a'", Object::Boolean(true)),
+            ("'a' != 'a'", Object::Boolean(false)),
+            ("'a' + 'b'", Object::String("ab".into())),
+            ("\"ab\" + 'c'", Object::String("abc".into())),
+            ("'\\n' + \"x\"", Object::String("\nx".into())),
+            ("\"<${'x'}>\"", Object::String("<x>".into())),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_logical_operators_short_circuit() -> Result<(), ()> {
+        let tests = vec![
+            ("1 < 2 && 2 < 3", Ok(Object::Boolean(true))),
+            ("1 < 2 && 3", Ok(Object::Boolean(true))),
+            ("1 > 2 || 2 > 3", Ok(Object::Boolean(false))),
+            // The right side is never looked at, so `missing` isn't an error.
+            ("1 > 2 && missing", Ok(Object::Boolean(false))),
+            ("1 < 2 || missing", Ok(Object::Boolean(true))),
+            (
+                "1 < 2 && missing",
+                Err(String::from("1:10: identifier not found: missing")),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != expected {
+                println!("{}: expected={:?} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_conditionals() -> Result<(), ()> {
+        let tests = vec![
+            ("if (true) { 10 }", Object::Integer(10)),
+            ("if (false) { 10 }", Object::Null),
+            ("if (1 < 2) { 10 } else { 20 }", Object::Integer(10)),
+            ("if (1 > 2) { 10 } else { 20 }", Object::Integer(20)),
+            (
+                "if (1 > 2) { 10 } else if (0) { 20 } else { 30 }",
+                Object::Integer(20),
+            ),
+            (
+                "let x = 5; if (x > 1) { let y = x * 2; y + 1 }",
+                Object::Integer(11),
+            ),
+            (
+                "if (true) { if (true) { return 1; } 2 } 3",
+                Object::Integer(1),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_functions() -> Result<(), ()> {
+        let tests = vec![
+            (
+                "let add = fn(a, b) { a + b }; add(1, 2 * 3)",
+                Object::Integer(7),
+            ),
+            ("fn(x) { x * 2 }(5)", Object::Integer(10)),
+            ("fn() { return 1; 2 }() + 1", Object::Integer(2)),
+            (
+                "let adder = fn(x) { fn(y) { x + y } }; adder(2)(3)",
+                Object::Integer(5),
+            ),
+            (
+                "let fact = fn(n) { if (n < 2) { 1 } else { n * fact(n - 1) } }; fact(5)",
+                Object::Integer(120),
+            ),
+            (
+                "let n = 0; let bump = fn() { n += 1 }; bump(); bump(); n",
+                Object::Integer(2),
+            ),
+            ("let x = 1; fn(x) { x }(2); x", Object::Integer(1)),
+            ("chr(ord('a') + 1)", Object::Char('b')),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrays() -> Result<(), ()> {
+        let tests = vec![
+            (
+                "[1, 2 * 2, \"three\",]",
+                Object::Array(vec![
+                    Object::Integer(1),
+                    Object::Integer(4),
+                    Object::String("three".into()),
+                ]),
+            ),
+            ("[1, 2, 3][0]", Object::Integer(1)),
+            ("let i = 1; [1, 2, 3][i + 1]", Object::Integer(3)),
+            ("[1, 2, 3][3]", Object::Null),
+            ("[1, 2, 3][-1]", Object::Null),
+            ("[[1], [2, 3]][1][0]", Object::Integer(2)),
+            ("[fn(x) { x * 2 }][0](4)", Object::Integer(8)),
+            ("\"héllo\"[1]", Object::Char('é')),
+            ("[1, 2] == [1, 2]", Object::Boolean(true)),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hashes() -> Result<(), ()> {
+        let tests = vec![
+            ("{}", Object::Hash(vec![])),
+            (
+                "let k = \"b\"; {\"a\": 1, k: 2, 3: true, 'c': [4]}",
+                Object::Hash(vec![
+                    (Object::String("a".into()), Object::Integer(1)),
+                    (Object::String("b".into()), Object::Integer(2)),
+                    (Object::Integer(3), Object::Boolean(true)),
+                    (Object::Char('c'), Object::Array(vec![Object::Integer(4)])),
+                ]),
+            ),
+            (
+                "{1: \"x\", 2: \"y\", 1: \"z\"}",
+                Object::Hash(vec![
+                    (Object::Integer(1), Object::String("z".into())),
+                    (Object::Integer(2), Object::String("y".into())),
+                ]),
+            ),
+            ("{\"a\": 1}[\"a\"]", Object::Integer(1)),
+            ("{true: 1 + 1}[1 < 2]", Object::Integer(2)),
+            ("{\"a\": 1}[\"b\"]", Object::Null),
+            ("{1: 2}[\"1\"]", Object::Null),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_loops() -> Result<(), ()> {
+        let tests = vec![
+            (
+                "let i = 0; while (i < 5) { i += 1; } i",
+                Object::Integer(5),
+            ),
+            (
+                "let i = 0; while (true) { i += 1; if (i == 3) { break; } } i",
+                Object::Integer(3),
+            ),
+            (
+                "let sum = 0; for (x in [1, 2, 3, 4]) { if (x % 2 == 0) { continue; } sum += x; } sum",
+                Object::Integer(4),
+            ),
+            (
+                "let s = \"\"; for (c in \"abc\") { s += c + '-'; } s",
+                Object::String("a-b-c-".into()),
+            ),
+            (
+                "let keys = \"\"; for (k in {\"b\": 1, \"a\": 2}) { keys += k; } keys",
+                Object::String("ba".into()),
+            ),
+            (
+                "let n = 0; for (x in [1, 2]) { for (y in [1, 2, 3]) { if (y == 2) { break; } n += 1; } } n",
+                Object::Integer(2),
+            ),
+            (
+                "let f = fn() { for (x in [1, 2, 3]) { if (x == 2) { return x; } } }; f()",
+                Object::Integer(2),
+            ),
+            ("for (x in []) { x }", Object::Null),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compound_assignment() -> Result<(), ()> {
+        let env = new_env();
+        env.borrow_mut().set("x".into(), Object::Integer(10));
+
+        let tests = vec![
+            ("x += 5", Object::Integer(15)),
+            ("x -= 3 * 2", Object::Integer(9)),
+            ("x *= 2", Object::Integer(18)),
+            ("x /= 4", Object::Integer(4)),
+            ("x", Object::Integer(4)),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval_with(input, &env);
+            if result != Ok(expected.clone()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_errors() -> Result<(), ()> {
+        let tests = vec![
+            ("1 / 0", "1:3: division by zero"),
+            ("5 % 0", "1:3: division by zero"),
+            ("9223372036854775807 + 1", "1:21: integer overflow"),
+            ("2 ** 64", "1:3: integer overflow"),
+            ("1 < 2 == 1", "1:7: type mismatch: BOOLEAN == INTEGER"),
+            ("y += 1", "1:1: identifier not found: y"),
+            (r#""a" - "b""#, "1:5: unknown operator: STRING - STRING"),
+            (r#""a" + 1"#, "1:5: type mismatch: STRING + INTEGER"),
+            ("'a' - 'b'", "1:5: unknown operator: CHAR - CHAR"),
+            ("'a' < \"b\"", "1:5: type mismatch: CHAR < STRING"),
+            (
+                "fn(x) { x }()",
+                "1:12: wrong number of arguments: got 0, want 1",
+            ),
+            ("let f = 1; f(2)", "1:13: not a function: INTEGER"),
+            (
+                "ord(1)",
+                "1:4: wrong arguments to `ord`: got (INTEGER), want (CHAR)",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let result = eval(input);
+            if result != Err(expected.to_string()) {
+                println!("{}: expected={} got={:?}", input, expected, result);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,214 +1,923 @@
-use crate::tokens::{Token, TokenType};
+use crate::span::{Position, Span};
+use crate::tokens::{lookup_keyword, Token, TokenType};
+use std::borrow::Cow;
+use std::fmt;
+use std::rc::Rc;
 
-pub struct Lexer {
+#[derive(Debug, PartialEq, Clone)]
+pub enum LexErrorKind {
+    UnterminatedString,
+    UnterminatedChar,
+    InvalidChar(String),
+    UnexpectedCharacter(char),
+    InvalidEscape(String),
+    InvalidNumber(String),
+    UnterminatedComment,
+    InvalidUtf8,
+    Io(String),
+}
+
+/// A problem found while lexing. The lexer records it and keeps going,
+/// handing out an `Illegal` token where it could not make sense of the input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.span)?;
+
+        match &self.kind {
+            LexErrorKind::UnterminatedString => write!(f, "string literal is never closed"),
+            LexErrorKind::UnterminatedChar => write!(f, "character literal is never closed"),
+            LexErrorKind::InvalidChar(literal) => {
+                write!(
+                    f,
+                    "character literal {} must hold exactly one character",
+                    literal
+                )
+            }
+            LexErrorKind::UnexpectedCharacter(ch) => write!(f, "unexpected character {:?}", ch),
+            LexErrorKind::InvalidEscape(escape) => {
+                write!(f, "invalid escape sequence {}", escape)
+            }
+            LexErrorKind::InvalidNumber(literal) => {
+                write!(f, "invalid numeric literal {}", literal)
+            }
+            LexErrorKind::UnterminatedComment => write!(f, "block comment is never closed"),
+            LexErrorKind::InvalidUtf8 => write!(f, "input is not valid UTF-8"),
+            LexErrorKind::Io(err) => write!(f, "could not read input: {}", err),
+        }
+    }
+}
+
+/// Lexes a borrowed source string. Token literals borrow from the input
+/// wherever they can, so only string literals containing escapes allocate.
+pub struct Lexer<'src> {
     pub position: usize,
     pub read_position: usize,
-    pub ch: u8,
-    pub input: Vec<u8>,
+    pub ch: char,
+    pub input: &'src str,
+    pub line: usize,
+    pub column: usize,
+    pub file: Option<Rc<str>>,
+    pub errors: Vec<LexError>,
+    /// Byte offset of `input` within the whole text, added to every span.
+    pub offset: usize,
+    /// One entry per `${` we are inside of: where its string literal starts,
+    /// and a count of the `{` opened within it, so we know which `}` goes back
+    /// to reading the string.
+    pub templates: Vec<(Position, usize)>,
+    /// Hand out whitespace and comments as `Whitespace` and `Comment` tokens
+    /// instead of skipping them, so the tokens cover every byte of input.
+    pub trivia: bool,
 }
 
-impl Lexer {
-    pub fn new(input: String) -> Self {
+impl<'src> Lexer<'src> {
+    pub fn new(input: &'src str) -> Self {
+        Self::resume(input, Position::default())
+    }
+
+    /// Creates a lexer for `input`, a piece of some larger text that begins
+    /// at `start`. Spans are reported relative to the larger text.
+    pub fn resume(input: &'src str, start: Position) -> Self {
         let mut l = Self {
             position: 0,
             read_position: 0,
-            ch: 0,
-            input: input.into_bytes(),
+            ch: '\0',
+            input,
+            line: start.line,
+            column: start.column,
+            file: None,
+            errors: Vec::new(),
+            offset: start.offset,
+            templates: Vec::new(),
+            trivia: false,
         };
 
-        l.next_token();
+        l.read_char();
+
+        l
+    }
 
+    /// Creates a lexer whose token spans report `file` as their origin.
+    pub fn with_file(input: &'src str, file: &str) -> Self {
+        let mut l = Self::new(input);
+        l.file = Some(file.into());
         l
     }
 
     pub fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            self.ch = 0;
-        } else {
-            self.ch = self.input[self.read_position];
+        if self.position >= self.input.len() {
+            return;
+        }
+
+        if self.read_position > 0 {
+            if self.ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
         }
 
+        self.ch = self.input[self.read_position..]
+            .chars()
+            .next()
+            .unwrap_or('\0');
+
         self.position = self.read_position;
-        self.read_position += 1;
+        self.read_position += self.ch.len_utf8();
+    }
+
+    pub fn next_token(&mut self) -> Token<'src> {
+        if !self.trivia {
+            self.skip_whitespace();
+        }
+
+        let start = self.location();
+        let trivia = if self.trivia {
+            self.read_trivia()
+        } else {
+            None
+        };
+        let mut token = match trivia {
+            Some(token) => token,
+            None => self.read_token(start),
+        };
+        token.span = Span::new(self.file.clone(), start, self.location());
+
+        token
+    }
+
+    pub fn errors(&self) -> &Vec<LexError> {
+        &self.errors
+    }
+
+    fn error(&mut self, kind: LexErrorKind, start: Position) {
+        let span = Span::new(self.file.clone(), start, self.location());
+        self.errors.push(LexError { kind, span });
     }
 
-    pub fn next_token(&mut self) -> Token {
-        self.skip_whitespace();
+    fn read_token(&mut self, start: Position) -> Token<'src> {
         let token = match self.ch {
-            b'=' => {
-                if self.peek_char() == b'=' {
+            '=' => {
+                if self.peek_char() == '=' {
                     self.read_char();
                     Token::new(TokenType::Equal, "==".into())
                 } else {
                     Token::new(TokenType::Assign, "=".into())
                 }
             }
-            b'"' => self.read_string(),
-            b';' => Token::new(TokenType::Semicolon, ";".into()),
-            b'(' => Token::new(TokenType::LParen, "(".into()),
-            b')' => Token::new(TokenType::RParen, ")".into()),
-            b',' => Token::new(TokenType::Comma, ",".into()),
-            b'+' => Token::new(TokenType::Plus, "+".into()),
-            b'{' => Token::new(TokenType::LBrace, "{".into()),
-            b'}' => Token::new(TokenType::RBrace, "}".into()),
-            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+            '"' => self.read_string(start),
+            '\'' => return self.read_char_literal(start),
+            ';' => Token::new(TokenType::Semicolon, ";".into()),
+            ':' => Token::new(TokenType::Colon, ":".into()),
+            '(' => Token::new(TokenType::LParen, "(".into()),
+            ')' => Token::new(TokenType::RParen, ")".into()),
+            ',' => Token::new(TokenType::Comma, ",".into()),
+            '[' => Token::new(TokenType::LBracket, "[".into()),
+            ']' => Token::new(TokenType::RBracket, "]".into()),
+            '+' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::new(TokenType::PlusAssign, "+=".into())
+                } else {
+                    Token::new(TokenType::Plus, "+".into())
+                }
+            }
+            '{' => {
+                if let Some((_, depth)) = self.templates.last_mut() {
+                    *depth += 1;
+                }
+                Token::new(TokenType::LBrace, "{".into())
+            }
+            '}' if matches!(self.templates.last(), Some((_, 0))) => {
+                let (origin, _) = self.templates.pop().unwrap();
+                self.read_string_part(
+                    start,
+                    origin,
+                    TokenType::TemplateTail,
+                    TokenType::TemplateMiddle,
+                )
+            }
+            '}' => {
+                if let Some((_, depth)) = self.templates.last_mut() {
+                    *depth -= 1;
+                }
+                Token::new(TokenType::RBrace, "}".into())
+            }
+            'r' if self.at_raw_string() => self.read_raw_string(start),
+            ch if is_identifier_start(ch) => {
                 let ident = self.read_identifier();
-                return match ident.as_str() {
-                    "fn" => Token::new(TokenType::Function, "fn".into()),
-                    "let" => Token::new(TokenType::Let, "let".into()),
-                    "return" => Token::new(TokenType::Return, "return".into()),
-                    "true" => Token::new(TokenType::True, "true".into()),
-                    "false" => Token::new(TokenType::False, "false".into()),
-                    "if" => Token::new(TokenType::If, "if".into()),
-                    "else" => Token::new(TokenType::Else, "else".into()),
-                    _ => Token::new(TokenType::Ident, ident),
-                };
-            }
-            b'0'..=b'9' => {
-                let number = self.read_int();
-
-                return Token::new(TokenType::Int, number);
-            }
-            b'-' => Token::new(TokenType::Minus, "-".into()),
-            b'!' => {
-                if self.peek_char() == b'=' {
+                let token_type = lookup_keyword(ident).unwrap_or(TokenType::Ident);
+                return Token::new(token_type, ident.into());
+            }
+            '0'..='9' => return self.read_number(start),
+            '-' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::new(TokenType::MinusAssign, "-=".into())
+                } else {
+                    Token::new(TokenType::Minus, "-".into())
+                }
+            }
+            '!' => {
+                if self.peek_char() == '=' {
                     self.read_char();
                     Token::new(TokenType::BangEqual, "!=".into())
                 } else {
                     Token::new(TokenType::Bang, "!".into())
                 }
             }
-            b'*' => Token::new(TokenType::Asterisk, "*".into()),
-            b'/' => {
-                if self.peek_char() == b'/' {
-                    return self.comment();
+            '*' => match self.peek_char() {
+                '*' => {
+                    self.read_char();
+                    Token::new(TokenType::Power, "**".into())
+                }
+                '=' => {
+                    self.read_char();
+                    Token::new(TokenType::AsteriskAssign, "*=".into())
+                }
+                _ => Token::new(TokenType::Asterisk, "*".into()),
+            },
+            '/' if self.at_doc_comment() => return self.read_doc_comment(),
+            '/' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::new(TokenType::SlashAssign, "/=".into())
+                } else {
+                    Token::new(TokenType::Slash, "/".into())
+                }
+            }
+            '%' => Token::new(TokenType::Percent, "%".into()),
+            '<' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::new(TokenType::LessEqual, "<=".into())
+                } else {
+                    Token::new(TokenType::LessThan, "<".into())
+                }
+            }
+            '>' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::new(TokenType::GreaterEqual, ">=".into())
+                } else {
+                    Token::new(TokenType::GreaterThan, ">".into())
+                }
+            }
+            '&' if self.peek_char() == '&' => {
+                self.read_char();
+                Token::new(TokenType::And, "&&".into())
+            }
+            '|' if self.peek_char() == '|' => {
+                self.read_char();
+                Token::new(TokenType::Or, "||".into())
+            }
+            '\0' => {
+                // Input ran out inside `${`, so the string it belongs to was
+                // never closed either.
+                if let Some(&(origin, _)) = self.templates.first() {
+                    self.templates.clear();
+                    self.error(LexErrorKind::UnterminatedString, origin);
                 }
-                Token::new(TokenType::Slash, "/".into())
+                Token::new(TokenType::Eof, "".into())
+            }
+            _ => {
+                let ch = self.ch;
+                self.read_char();
+                self.error(LexErrorKind::UnexpectedCharacter(ch), start);
+
+                let literal = &self.input[start.offset - self.offset..self.position];
+                return Token::new(TokenType::Illegal, literal.into());
             }
-            b'<' => Token::new(TokenType::LessThan, "<".into()),
-            b'>' => Token::new(TokenType::GreaterThan, ">".into()),
-            0 => Token::new(TokenType::Eof, "".into()),
-            _ => Token::new(TokenType::Illegal, "".into()),
         };
 
         self.read_char();
         token
     }
 
-    fn comment(&mut self) -> Token {
-        while self.ch != b'\n' && self.ch != 0 {
+    fn location(&self) -> Position {
+        Position::new(self.offset + self.position, self.line, self.column)
+    }
+
+    fn comment(&mut self) {
+        while self.ch != '\n' && self.ch != '\0' {
             self.read_char()
         }
+    }
+
+    /// Skips a `/* ... */` comment, which may contain nested block comments.
+    fn block_comment(&mut self) {
+        let start = self.location();
+        let mut depth = 0;
+
+        loop {
+            if self.ch == '\0' {
+                self.error(LexErrorKind::UnterminatedComment, start);
+                return;
+            }
+
+            if self.ch == '/' && self.peek_char() == '*' {
+                depth += 1;
+                self.read_char();
+            } else if self.ch == '*' && self.peek_char() == '/' {
+                depth -= 1;
+                self.read_char();
+
+                if depth == 0 {
+                    self.read_char();
+                    return;
+                }
+            }
+
+            self.read_char();
+        }
+    }
+
+    /// `///` starts a doc comment, but `////` is an ordinary line comment.
+    fn at_doc_comment(&self) -> bool {
+        let rest = &self.input[self.position..];
+        rest.starts_with("///") && !rest.starts_with("////")
+    }
+
+    fn read_doc_comment(&mut self) -> Token<'src> {
+        for _ in 0..3 {
+            self.read_char();
+        }
+
+        if self.ch == ' ' {
+            self.read_char();
+        }
+
+        let position = self.position;
+        self.comment();
+
+        let text = self.input[position..self.position].trim_end_matches('\r');
+        Token::new(TokenType::DocComment, text.into())
+    }
+
+    fn read_string(&mut self, start: Position) -> Token<'src> {
+        if self.input[self.position..].starts_with("\"\"\"") {
+            return self.read_multiline_string(start);
+        }
+
+        self.read_string_part(start, start, TokenType::String, TokenType::TemplateHead)
+    }
 
-        self.next_token()
+    /// `r"` or `r#"`, `r##"`, ...
+    fn at_raw_string(&self) -> bool {
+        self.input[self.read_position..]
+            .trim_start_matches('#')
+            .starts_with('"')
     }
 
-    fn read_string(&mut self) -> Token {
+    /// Reads `r"..."` or `r#"..."#`. Nothing inside is an escape, and the
+    /// string only ends at a `"` followed by as many `#` as it was opened
+    /// with, so `r#"say "hi""#` is `say "hi"`.
+    fn read_raw_string(&mut self, start: Position) -> Token<'src> {
         self.read_char();
+
+        let mut hashes = 0;
+        while self.ch == '#' {
+            hashes += 1;
+            self.read_char();
+        }
+
+        let closing = format!("\"{}", "#".repeat(hashes));
+        let contents = self.read_position;
+
+        let Some(len) = self.input[contents..].find(&closing) else {
+            while self.ch != '\0' {
+                self.read_char();
+            }
+            self.error(LexErrorKind::UnterminatedString, start);
+            return Token::new(TokenType::Illegal, self.input[contents..].into());
+        };
+
+        // Leave the lexer on the last character of the closing delimiter.
+        while self.position < contents + len + hashes {
+            self.read_char();
+        }
+
+        Token::new(
+            TokenType::String,
+            self.input[contents..contents + len].into(),
+        )
+    }
+
+    /// Reads a `"""` string, which may span lines and strips the indentation
+    /// common to all of its lines. A line break straight after the opening
+    /// quotes and a whitespace-only line before the closing quotes are not
+    /// part of the string. Escapes work as in ordinary strings, but `${` is
+    /// not an interpolation.
+    fn read_multiline_string(&mut self, start: Position) -> Token<'src> {
+        for _ in 0..3 {
+            self.read_char();
+        }
+
+        let contents = self.position;
+        let Some(len) = find_closing_quotes(&self.input[contents..]) else {
+            while self.ch != '\0' {
+                self.read_char();
+            }
+            self.error(LexErrorKind::UnterminatedString, start);
+            return Token::new(TokenType::Illegal, self.input[contents..].into());
+        };
+
         let mut literal = String::new();
 
-        while self.ch != b'"' && self.ch != 0 {
-            if self.ch == b'\\' {
+        for (i, (from, to)) in dedented_lines(self.input, contents, contents + len)
+            .into_iter()
+            .enumerate()
+        {
+            if i > 0 {
+                literal.push('\n');
+            }
+
+            while self.position < from {
                 self.read_char();
             }
-            literal.push(self.ch as char);
-            self.read_char()
+
+            while self.position < to {
+                if self.ch == '\\' {
+                    if let Some(ch) = self.read_escape() {
+                        literal.push(ch);
+                    }
+                } else {
+                    literal.push(self.ch);
+                    self.read_char();
+                }
+            }
+        }
+
+        // Leave the lexer on the last of the closing quotes.
+        while self.position < contents + len + 2 {
+            self.read_char();
         }
 
-        if self.ch == 0 {
-            panic!("String didn't close");
+        Token::new(TokenType::String, literal.into())
+    }
+
+    /// Reads string text starting just after an opening `"`, or after the `}`
+    /// that ends an interpolation. Returns a `closed` token if the text runs
+    /// up to a closing `"`, or an `interpolated` token if it stops at `${`.
+    /// `origin` is where the whole string literal starts.
+    fn read_string_part(
+        &mut self,
+        start: Position,
+        origin: Position,
+        closed: TokenType,
+        interpolated: TokenType,
+    ) -> Token<'src> {
+        self.read_char();
+        let input = self.input;
+        let position = self.position;
+
+        // Only allocate once an escape means the literal differs from the source.
+        let mut cooked: Option<String> = None;
+
+        while self.ch != '"' && self.ch != '\0' && !self.at_interpolation() {
+            if self.ch == '\\' {
+                let literal =
+                    cooked.get_or_insert_with(|| input[position..self.position].to_string());
+                if let Some(ch) = self.read_escape() {
+                    literal.push(ch);
+                }
+                continue;
+            }
+            if let Some(literal) = &mut cooked {
+                literal.push(self.ch);
+            }
+            self.read_char()
         }
 
-        Token::new(TokenType::String, literal)
+        let literal = match cooked {
+            Some(literal) => Cow::Owned(literal),
+            None => Cow::Borrowed(&input[position..self.position]),
+        };
+
+        match self.ch {
+            '\0' => {
+                self.error(LexErrorKind::UnterminatedString, start);
+                Token::new(TokenType::Illegal, literal)
+            }
+            '"' => Token::new(closed, literal),
+            _ => {
+                // Step onto the `{`; read_token moves past it as usual.
+                self.read_char();
+                self.templates.push((origin, 0));
+                Token::new(interpolated, literal)
+            }
+        }
     }
 
-    fn read_identifier(&mut self) -> String {
+    /// Reads `'a'` or an escape such as `'\n'`, leaving the lexer just past
+    /// the closing quote. Anything but exactly one character is an error.
+    fn read_char_literal(&mut self, start: Position) -> Token<'src> {
+        self.read_char();
         let position = self.position;
 
-        while self.ch.is_ascii_alphabetic() || self.ch == b'_' {
+        // An invalid escape has already been reported by read_escape.
+        let mut reported = false;
+
+        let literal = match self.ch {
+            '\'' | '\n' | '\0' => None,
+            '\\' => {
+                let ch = self.read_escape();
+                reported = ch.is_none();
+                ch.map(|ch| Cow::Owned(ch.to_string()))
+            }
+            _ => {
+                self.read_char();
+                Some(Cow::Borrowed(&self.input[position..self.position]))
+            }
+        };
+
+        if self.ch == '\'' {
+            if let Some(literal) = literal {
+                self.read_char();
+                return Token::new(TokenType::Char, literal);
+            }
+        }
+
+        while self.ch != '\'' && self.ch != '\n' && self.ch != '\0' {
             self.read_char();
         }
 
-        String::from_utf8(self.input[position..self.position].to_vec()).unwrap()
+        let contents = &self.input[position..self.position];
+        if self.ch == '\'' {
+            self.read_char();
+            if !reported {
+                let literal = self.input[start.offset - self.offset..self.position].to_string();
+                self.error(LexErrorKind::InvalidChar(literal), start);
+            }
+        } else {
+            self.error(LexErrorKind::UnterminatedChar, start);
+        }
+
+        Token::new(TokenType::Illegal, contents.into())
+    }
+
+    fn at_interpolation(&self) -> bool {
+        self.ch == '$' && self.peek_char() == '{'
     }
 
-    fn read_int(&mut self) -> String {
+    /// Reads the escape sequence starting at the current backslash and leaves
+    /// the lexer on the first character after it. Returns `None` for an
+    /// invalid escape, which is recorded as an error.
+    fn read_escape(&mut self) -> Option<char> {
+        let start = self.location();
         let position = self.position;
+        self.read_char();
+
+        let ch = match self.ch {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\\' => Some('\\'),
+            '"' => Some('"'),
+            '\'' => Some('\''),
+            '$' => Some('$'),
+            'x' => self.read_hex_escape(),
+            'u' => self.read_unicode_escape(),
+            '\0' => return None,
+            _ => None,
+        };
+        self.read_char();
+
+        if ch.is_none() {
+            let escape = self.input[position..self.position].to_string();
+            self.error(LexErrorKind::InvalidEscape(escape), start);
+        }
+
+        ch
+    }
+
+    /// `\x7F`: exactly two hex digits, limited to ASCII.
+    fn read_hex_escape(&mut self) -> Option<char> {
+        let mut value = 0;
 
-        while self.ch.is_ascii_digit() {
+        for _ in 0..2 {
+            let digit = self.peek_char().to_digit(16)?;
             self.read_char();
+            value = value * 16 + digit;
         }
 
-        String::from_utf8(self.input[position..self.position].to_vec()).unwrap()
+        if value > 0x7F {
+            return None;
+        }
+
+        char::from_u32(value)
     }
 
-    fn skip_whitespace(&mut self) {
-        while self.ch.is_ascii_whitespace() {
+    /// `\u{1F600}`: one to six hex digits naming a Unicode scalar value.
+    fn read_unicode_escape(&mut self) -> Option<char> {
+        if self.peek_char() != '{' {
+            return None;
+        }
+        self.read_char();
+
+        let mut value = 0;
+        let mut digits = 0;
+
+        while let Some(digit) = self.peek_char().to_digit(16) {
+            if digits == 6 {
+                return None;
+            }
             self.read_char();
+            value = value * 16 + digit;
+            digits += 1;
         }
+
+        if digits == 0 || self.peek_char() != '}' {
+            return None;
+        }
+        self.read_char();
+
+        char::from_u32(value)
     }
 
-    fn peek_char(&self) -> u8 {
-        if self.read_position >= self.input.len() {
-            0
-        } else {
-            self.input[self.read_position]
+    fn read_identifier(&mut self) -> &'src str {
+        let position = self.position;
+
+        while is_identifier_continue(self.ch) {
+            self.read_char();
         }
+
+        &self.input[position..self.position]
     }
-}
 
-#[cfg(test)]
-mod test {
-    #[test]
-    fn get_next_token() -> Result<(), ()> {
-        let input = String::from(
-            "let five = 5;
-        let ten = 10;
-        let add = fn(x, y) {
-            x + y;
+    /// Reads an integer or float literal. The token keeps the literal exactly
+    /// as written (radix prefix and `_` separators included); turning it into
+    /// a value, and checking that it fits, is left to the parser.
+    fn read_number(&mut self, start: Position) -> Token<'src> {
+        let position = self.position;
+
+        let radix = match (self.ch, self.peek_char()) {
+            ('0', 'x' | 'X') => 16,
+            ('0', 'o' | 'O') => 8,
+            ('0', 'b' | 'B') => 2,
+            _ => 10,
         };
-        let result = add(five, ten);
-        !-/*5;
-        5 < 10 > 5;
 
-        if (5 < 10) {
-            return true;
+        let (token_type, valid) = if radix == 10 {
+            self.read_decimal()
         } else {
-            return false;
+            self.read_char();
+            self.read_char();
+
+            let digits = self.position;
+            while self.ch.is_ascii_alphanumeric() || self.ch == '_' {
+                self.read_char();
+            }
+
+            let digits = &self.input[digits..self.position];
+            let valid = digits.chars().any(|ch| ch != '_')
+                && digits.chars().all(|ch| ch == '_' || ch.is_digit(radix));
+
+            (TokenType::Int, valid)
+        };
+
+        // Letters running straight on from the digits, as in `123abc`, make
+        // one malformed literal rather than a number followed by a name.
+        let mut valid = valid;
+        while is_identifier_continue(self.ch) {
+            self.read_char();
+            valid = false;
         }
 
-        10 == 10;
-        10 != 9;
-        ",
-        );
+        let literal = &self.input[position..self.position];
 
-        let mut lexer = super::Lexer::new(input);
+        if !valid {
+            self.error(LexErrorKind::InvalidNumber(literal.to_string()), start);
+            return Token::new(TokenType::Illegal, literal.into());
+        }
 
-        let tests = vec![
-            (super::TokenType::Let, "let"),
-            (super::TokenType::Ident, "five"),
-            (super::TokenType::Assign, "="),
-            (super::TokenType::Int, "5"),
-            (super::TokenType::Semicolon, ";"),
-            (super::TokenType::Let, "let"),
-            (super::TokenType::Ident, "ten"),
-            (super::TokenType::Assign, "="),
-            (super::TokenType::Int, "10"),
-            (super::TokenType::Semicolon, ";"),
-            (super::TokenType::Let, "let"),
-            (super::TokenType::Ident, "add"),
-            (super::TokenType::Assign, "="),
-            (super::TokenType::Function, "fn"),
-            (super::TokenType::LParen, "("),
-            (super::TokenType::Ident, "x"),
-            (super::TokenType::Comma, ","),
-            (super::TokenType::Ident, "y"),
-            (super::TokenType::RParen, ")"),
-            (super::TokenType::LBrace, "{"),
-            (super::TokenType::Ident, "x"),
-            (super::TokenType::Plus, "+"),
-            (super::TokenType::Ident, "y"),
-            (super::TokenType::Semicolon, ";"),
+        Token::new(token_type, literal.into())
+    }
+
+    /// `1_000`, `3.14`, `1e-9`. A fraction needs a digit after the dot, while
+    /// an exponent, once started, must be followed by digits.
+    fn read_decimal(&mut self) -> (TokenType, bool) {
+        let mut token_type = TokenType::Int;
+        self.read_digits();
+
+        if self.ch == '.' && self.peek_char().is_ascii_digit() {
+            token_type = TokenType::Float;
+            self.read_char();
+            self.read_digits();
+        }
+
+        if self.ch == 'e' || self.ch == 'E' {
+            token_type = TokenType::Float;
+            self.read_char();
+
+            if self.ch == '+' || self.ch == '-' {
+                self.read_char();
+            }
+
+            if !self.ch.is_ascii_digit() {
+                return (token_type, false);
+            }
+            self.read_digits();
+        }
+
+        (token_type, true)
+    }
+
+    fn read_digits(&mut self) {
+        while self.ch.is_ascii_digit() || self.ch == '_' {
+            self.read_char();
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.read_trivia().is_some() {}
+    }
+
+    /// Reads a run of whitespace or a single comment, if there is one here.
+    fn read_trivia(&mut self) -> Option<Token<'src>> {
+        let position = self.position;
+
+        let token_type = if self.ch.is_whitespace() {
+            while self.ch.is_whitespace() {
+                self.read_char();
+            }
+            TokenType::Whitespace
+        } else if self.ch == '/' && self.peek_char() == '/' && !self.at_doc_comment() {
+            self.comment();
+            TokenType::Comment
+        } else if self.ch == '/' && self.peek_char() == '*' {
+            self.block_comment();
+            TokenType::Comment
+        } else {
+            return None;
+        };
+
+        Some(Token::new(
+            token_type,
+            self.input[position..self.position].into(),
+        ))
+    }
+
+    fn peek_char(&self) -> char {
+        self.input
+            .get(self.read_position..)
+            .and_then(|rest| rest.chars().next())
+            .unwrap_or('\0')
+    }
+}
+
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Token<'src>;
+
+    /// Yields every token up to, but not including, `Eof`.
+    fn next(&mut self) -> Option<Token<'src>> {
+        let token = self.next_token();
+
+        if token.token_type == TokenType::Eof {
+            None
+        } else {
+            Some(token)
+        }
+    }
+}
+
+/// Byte offset of the `"""` closing a multi-line string body, skipping over
+/// escaped characters.
+fn find_closing_quotes(body: &str) -> Option<usize> {
+    let mut chars = body.char_indices();
+
+    while let Some((i, ch)) = chars.next() {
+        if ch == '\\' {
+            chars.next();
+        } else if body[i..].starts_with("\"\"\"") {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
+/// Splits the body of a multi-line string, `input[from..to]`, into the byte
+/// ranges that make up its value: each kept line minus the common
+/// indentation, without its line break. The value joins them with `\n`
+/// whether the source used `\n` or `\r\n`.
+fn dedented_lines(input: &str, from: usize, to: usize) -> Vec<(usize, usize)> {
+    let is_blank = |line: &str| line.trim_matches([' ', '\t']).is_empty();
+    let indent_of = |line: &str| line.len() - line.trim_start_matches([' ', '\t']).len();
+
+    let mut lines = Vec::new();
+    let mut line_start = from;
+    let mut rest = input[from..to].split('\n').peekable();
+    while let Some(line) = rest.next() {
+        let text = match rest.peek() {
+            Some(_) => line.strip_suffix('\r').unwrap_or(line),
+            None => line,
+        };
+        lines.push((line_start, text));
+        line_start += line.len() + 1;
+    }
+
+    if lines.len() > 1 && is_blank(lines[0].1) {
+        lines.remove(0);
+    }
+    if lines.len() > 1 && is_blank(lines[lines.len() - 1].1) {
+        lines.pop();
+    }
+
+    // The longest run of leading whitespace that every non-blank line shares.
+    let mut common: Option<&str> = None;
+    for &(_, line) in lines.iter().filter(|(_, line)| !is_blank(line)) {
+        let indent = &line[..indent_of(line)];
+        let shared = match common {
+            None => indent,
+            Some(common) => {
+                let len = common
+                    .bytes()
+                    .zip(indent.bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                &common[..len]
+            }
+        };
+        common = Some(shared);
+    }
+    let common = common.map_or(0, str::len);
+
+    lines
+        .iter()
+        .map(|&(start, line)| (start + indent_of(line).min(common), start + line.len()))
+        .collect()
+}
+
+fn is_identifier_start(ch: char) -> bool {
+    ch == '_' || unicode_ident::is_xid_start(ch)
+}
+
+fn is_identifier_continue(ch: char) -> bool {
+    ch == '_' || unicode_ident::is_xid_continue(ch)
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    #[test]
+    fn get_next_token() -> Result<(), ()> {
+        let input = String::from(
+            "let five = 5;
+        let ten = 10;
+        let add = fn(x, y) {
+            x + y;
+        };
+        let result = add(five, ten);
+        !-/*5;
+        5 < 10 > 5;
+
+        if (5 < 10) {
+            return true;
+        } else {
+            return false;
+        }
+
+        10 == 10;
+        10 != 9;
+        ",
+        );
+
+        let mut lexer = super::Lexer::new(&input);
+
+        let tests = vec![
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Ident, "five"),
+            (super::TokenType::Assign, "="),
+            (super::TokenType::Int, "5"),
+            (super::TokenType::Semicolon, ";"),
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Ident, "ten"),
+            (super::TokenType::Assign, "="),
+            (super::TokenType::Int, "10"),
+            (super::TokenType::Semicolon, ";"),
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Ident, "add"),
+            (super::TokenType::Assign, "="),
+            (super::TokenType::Function, "fn"),
+            (super::TokenType::LParen, "("),
+            (super::TokenType::Ident, "x"),
+            (super::TokenType::Comma, ","),
+            (super::TokenType::Ident, "y"),
+            (super::TokenType::RParen, ")"),
+            (super::TokenType::LBrace, "{"),
+            (super::TokenType::Ident, "x"),
+            (super::TokenType::Plus, "+"),
+            (super::TokenType::Ident, "y"),
+            (super::TokenType::Semicolon, ";"),
             (super::TokenType::RBrace, "}"),
             (super::TokenType::Semicolon, ";"),
             (super::TokenType::Let, "let"),
@@ -236,4 +945,788 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn token_spans() -> Result<(), ()> {
+        let input = String::from("let x = 5;\n// note\n  x == 10;");
+
+        let mut lexer = super::Lexer::with_file(&input, "main.mk");
+
+        // (token type, line, column, start offset, end offset)
+        let tests = vec![
+            (super::TokenType::Let, 1, 1, 0, 3),
+            (super::TokenType::Ident, 1, 5, 4, 5),
+            (super::TokenType::Assign, 1, 7, 6, 7),
+            (super::TokenType::Int, 1, 9, 8, 9),
+            (super::TokenType::Semicolon, 1, 10, 9, 10),
+            (super::TokenType::Ident, 3, 3, 21, 22),
+            (super::TokenType::Equal, 3, 5, 23, 25),
+            (super::TokenType::Int, 3, 8, 26, 28),
+            (super::TokenType::Semicolon, 3, 10, 28, 29),
+            (super::TokenType::Eof, 3, 11, 29, 29),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            let span = &tok.span;
+
+            if tok.token_type != expected.0
+                || span.start.line != expected.1
+                || span.start.column != expected.2
+                || span.start.offset != expected.3
+                || span.end.offset != expected.4
+            {
+                return Err(());
+            }
+
+            if span.file.as_deref() != Some("main.mk") {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn lex_errors() -> Result<(), ()> {
+        let input = String::from("let a = 5 @ 3;\nlet b = 0b102 + 1e;\nlet c = \"open");
+
+        let mut lexer = super::Lexer::new(&input);
+
+        let tests = vec![
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Ident, "a"),
+            (super::TokenType::Assign, "="),
+            (super::TokenType::Int, "5"),
+            (super::TokenType::Illegal, "@"),
+            (super::TokenType::Int, "3"),
+            (super::TokenType::Semicolon, ";"),
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Ident, "b"),
+            (super::TokenType::Assign, "="),
+            (super::TokenType::Illegal, "0b102"),
+            (super::TokenType::Plus, "+"),
+            (super::TokenType::Illegal, "1e"),
+            (super::TokenType::Semicolon, ";"),
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Ident, "c"),
+            (super::TokenType::Assign, "="),
+            (super::TokenType::Illegal, "open"),
+            (super::TokenType::Eof, ""),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            if tok.token_type != expected.0 || tok.literal != expected.1 {
+                return Err(());
+            }
+        }
+
+        let errors = [
+            (super::LexErrorKind::UnexpectedCharacter('@'), 1, 11),
+            (super::LexErrorKind::InvalidNumber("0b102".into()), 2, 9),
+            (super::LexErrorKind::InvalidNumber("1e".into()), 2, 17),
+            (super::LexErrorKind::UnterminatedString, 3, 9),
+        ];
+
+        if lexer.errors().len() != errors.len() {
+            println!("expected {} errors, got {:?}", errors.len(), lexer.errors());
+            return Err(());
+        }
+
+        for (error, expected) in lexer.errors().iter().zip(errors.iter()) {
+            println!("expected: {:?}, got: {}", expected, error);
+            if error.kind != expected.0
+                || error.span.start.line != expected.1
+                || error.span.start.column != expected.2
+            {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn string_escapes() -> Result<(), ()> {
+        let tests = vec![
+            (r#""a\nb""#, "a\nb"),
+            (r#""a\tb""#, "a\tb"),
+            (r#""a\rb""#, "a\rb"),
+            (r#""a\0b""#, "a\0b"),
+            (r#""a\\b""#, "a\\b"),
+            (r#""a\"b""#, "a\"b"),
+            (r#""\x41\x7F""#, "A\x7F"),
+            (r#""\u{e9}\u{1F600}""#, "\u{e9}\u{1F600}"),
+            (r#""\${x} $x""#, "${x} $x"),
+        ];
+
+        for (input, expected) in tests {
+            let mut lexer = super::Lexer::new(input);
+            let tok = lexer.next_token();
+            println!("input: {}, got: {:?}", input, tok);
+
+            if tok.token_type != super::TokenType::String || tok.literal != expected {
+                return Err(());
+            }
+
+            if !lexer.errors().is_empty() {
+                println!("unexpected errors: {:?}", lexer.errors());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_string_escapes() -> Result<(), ()> {
+        // (input, reported escape, column of the backslash)
+        let tests = vec![
+            (r#""ab\q""#, r"\q", 4),
+            (r#""\x80""#, r"\x80", 2),
+            (r#""\xZ1""#, r"\x", 2),
+            (r#""\u00e9""#, r"\u", 2),
+            (r#""\u{}""#, r"\u{", 2),
+            (r#""\u{D800}""#, r"\u{D800}", 2),
+            (r#""\u{1234567}""#, r"\u{123456", 2),
+        ];
+
+        for (input, escape, column) in tests {
+            let mut lexer = super::Lexer::new(input);
+            let tok = lexer.next_token();
+            println!("input: {}, got: {:?} {:?}", input, tok, lexer.errors());
+
+            if tok.token_type != super::TokenType::String || lexer.errors().len() != 1 {
+                return Err(());
+            }
+
+            let error = &lexer.errors()[0];
+            if error.kind != super::LexErrorKind::InvalidEscape(escape.into())
+                || error.span.start.column != column
+                || error.span.end.column != column + escape.len()
+            {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn keywords() -> Result<(), ()> {
+        let input =
+            "fn let return true false if else while for in break continue null const match import";
+
+        let tests = [
+            (super::TokenType::Function, "fn"),
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Return, "return"),
+            (super::TokenType::True, "true"),
+            (super::TokenType::False, "false"),
+            (super::TokenType::If, "if"),
+            (super::TokenType::Else, "else"),
+            (super::TokenType::While, "while"),
+            (super::TokenType::For, "for"),
+            (super::TokenType::In, "in"),
+            (super::TokenType::Break, "break"),
+            (super::TokenType::Continue, "continue"),
+            (super::TokenType::Null, "null"),
+            (super::TokenType::Const, "const"),
+            (super::TokenType::Match, "match"),
+            (super::TokenType::Import, "import"),
+            (super::TokenType::Eof, ""),
+        ];
+
+        let mut lexer = super::Lexer::new(input);
+
+        for (expected_type, expected_literal) in tests {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected_type, tok);
+
+            if tok.token_type != expected_type || tok.literal != expected_literal {
+                return Err(());
+            }
+        }
+
+        // Keywords are whole words only.
+        let mut lexer = super::Lexer::new("iffy format inner null_value");
+        for tok in lexer.by_ref() {
+            if tok.token_type != super::TokenType::Ident {
+                println!("expected an identifier, got: {:?}", tok);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn char_literals() -> Result<(), ()> {
+        let tests = [
+            (r"'a'", "a"),
+            (r"'é'", "é"),
+            (r"'\n'", "\n"),
+            (r"'\''", "'"),
+            (r"'\\'", "\\"),
+            (r#"'"'"#, "\""),
+            (r"'\u{00e9}'", "é"),
+        ];
+
+        for (input, expected) in tests {
+            let mut lexer = super::Lexer::new(input);
+            let tok = lexer.next_token();
+            println!("input: {}, got: {:?}", input, tok);
+
+            if tok.token_type != super::TokenType::Char || tok.literal != expected {
+                return Err(());
+            }
+
+            if !lexer.errors().is_empty() || lexer.next_token().token_type != super::TokenType::Eof
+            {
+                println!("unexpected errors: {:?}", lexer.errors());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_char_literals() -> Result<(), ()> {
+        let tests = [
+            ("''", super::LexErrorKind::InvalidChar("''".into())),
+            ("'ab'", super::LexErrorKind::InvalidChar("'ab'".into())),
+            ("'a", super::LexErrorKind::UnterminatedChar),
+            ("'a\n'", super::LexErrorKind::UnterminatedChar),
+            (r"'\q'", super::LexErrorKind::InvalidEscape(r"\q".into())),
+        ];
+
+        for (input, kind) in tests {
+            let mut lexer = super::Lexer::new(input);
+            let tok = lexer.next_token();
+            println!("input: {:?}, got: {:?} {:?}", input, tok, lexer.errors());
+
+            if tok.token_type != super::TokenType::Illegal
+                || lexer.errors().len() != 1
+                || lexer.errors()[0].kind != kind
+            {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn unicode_input() -> Result<(), ()> {
+        let input = String::from("let größe = \"héllo, 世界 😀\";\nπ € ñame");
+
+        let mut lexer = super::Lexer::new(&input);
+
+        // (token type, literal, line, column)
+        let tests = vec![
+            (super::TokenType::Let, "let", 1, 1),
+            (super::TokenType::Ident, "größe", 1, 5),
+            (super::TokenType::Assign, "=", 1, 11),
+            (super::TokenType::String, "héllo, 世界 😀", 1, 13),
+            (super::TokenType::Semicolon, ";", 1, 26),
+            (super::TokenType::Ident, "π", 2, 1),
+            (super::TokenType::Illegal, "€", 2, 3),
+            (super::TokenType::Ident, "ñame", 2, 5),
+            (super::TokenType::Eof, "", 2, 9),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+
+            if tok.token_type != expected.0
+                || tok.literal != expected.1
+                || tok.span.start.line != expected.2
+                || tok.span.start.column != expected.3
+            {
+                return Err(());
+            }
+        }
+
+        if lexer.errors().len() != 1
+            || lexer.errors()[0].kind != super::LexErrorKind::UnexpectedCharacter('€')
+        {
+            println!("unexpected errors: {:?}", lexer.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn identifiers_with_digits() -> Result<(), ()> {
+        let input = String::from("x1 item2 v2_final _0 9lives e\u{301}1");
+
+        let mut lexer = super::Lexer::new(&input);
+
+        let tests = vec![
+            (super::TokenType::Ident, "x1"),
+            (super::TokenType::Ident, "item2"),
+            (super::TokenType::Ident, "v2_final"),
+            (super::TokenType::Ident, "_0"),
+            (super::TokenType::Illegal, "9lives"),
+            (super::TokenType::Ident, "e\u{301}1"),
+            (super::TokenType::Eof, ""),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            if tok.token_type != expected.0 || tok.literal != expected.1 {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn numeric_literals() -> Result<(), ()> {
+        let input =
+            String::from("3.14 1e-9 2.5E+3 6e2 0xFF 0o755 0b1010 1_000_000 0x_dead_BEEF 7 1.max");
+
+        let mut lexer = super::Lexer::new(&input);
+
+        let tests = vec![
+            (super::TokenType::Float, "3.14"),
+            (super::TokenType::Float, "1e-9"),
+            (super::TokenType::Float, "2.5E+3"),
+            (super::TokenType::Float, "6e2"),
+            (super::TokenType::Int, "0xFF"),
+            (super::TokenType::Int, "0o755"),
+            (super::TokenType::Int, "0b1010"),
+            (super::TokenType::Int, "1_000_000"),
+            (super::TokenType::Int, "0x_dead_BEEF"),
+            (super::TokenType::Int, "7"),
+            (super::TokenType::Int, "1"),
+            (super::TokenType::Illegal, "."),
+            (super::TokenType::Ident, "max"),
+            (super::TokenType::Eof, ""),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            if tok.token_type != expected.0 || tok.literal != expected.1 {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_numeric_literals() -> Result<(), ()> {
+        let tests = vec![
+            "0x", "0b_", "0b2", "0o78", "0xFG", "1e", "1.5e+", "123abc", "1_000x", "2.5f", "0b1z",
+        ];
+
+        for input in tests {
+            let mut lexer = super::Lexer::new(input);
+            let tok = lexer.next_token();
+            println!("input: {}, got: {:?} {:?}", input, tok, lexer.errors());
+
+            if tok.token_type != super::TokenType::Illegal || tok.literal != input {
+                return Err(());
+            }
+
+            if lexer.errors().len() != 1
+                || lexer.errors()[0].kind != super::LexErrorKind::InvalidNumber(input.into())
+            {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn operators() -> Result<(), ()> {
+        let input =
+            String::from("a <= b >= c && d || e % f ** g; x += 1 -= 2 *= 3 /= 4; * / < > & |");
+
+        let mut lexer = super::Lexer::new(&input);
+
+        let tests = vec![
+            (super::TokenType::Ident, "a"),
+            (super::TokenType::LessEqual, "<="),
+            (super::TokenType::Ident, "b"),
+            (super::TokenType::GreaterEqual, ">="),
+            (super::TokenType::Ident, "c"),
+            (super::TokenType::And, "&&"),
+            (super::TokenType::Ident, "d"),
+            (super::TokenType::Or, "||"),
+            (super::TokenType::Ident, "e"),
+            (super::TokenType::Percent, "%"),
+            (super::TokenType::Ident, "f"),
+            (super::TokenType::Power, "**"),
+            (super::TokenType::Ident, "g"),
+            (super::TokenType::Semicolon, ";"),
+            (super::TokenType::Ident, "x"),
+            (super::TokenType::PlusAssign, "+="),
+            (super::TokenType::Int, "1"),
+            (super::TokenType::MinusAssign, "-="),
+            (super::TokenType::Int, "2"),
+            (super::TokenType::AsteriskAssign, "*="),
+            (super::TokenType::Int, "3"),
+            (super::TokenType::SlashAssign, "/="),
+            (super::TokenType::Int, "4"),
+            (super::TokenType::Semicolon, ";"),
+            (super::TokenType::Asterisk, "*"),
+            (super::TokenType::Slash, "/"),
+            (super::TokenType::LessThan, "<"),
+            (super::TokenType::GreaterThan, ">"),
+            (super::TokenType::Illegal, "&"),
+            (super::TokenType::Illegal, "|"),
+            (super::TokenType::Eof, ""),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            if tok.token_type != expected.0 || tok.literal != expected.1 {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn brackets() -> Result<(), ()> {
+        let input = String::from("[1, [2]][0]");
+
+        let mut lexer = super::Lexer::new(&input);
+
+        let tests = vec![
+            (super::TokenType::LBracket, "["),
+            (super::TokenType::Int, "1"),
+            (super::TokenType::Comma, ","),
+            (super::TokenType::LBracket, "["),
+            (super::TokenType::Int, "2"),
+            (super::TokenType::RBracket, "]"),
+            (super::TokenType::RBracket, "]"),
+            (super::TokenType::LBracket, "["),
+            (super::TokenType::Int, "0"),
+            (super::TokenType::RBracket, "]"),
+            (super::TokenType::Eof, ""),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            if tok.token_type != expected.0 || tok.literal != expected.1 {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn comments() -> Result<(), ()> {
+        let input = String::from(
+            "/* block */ let /* outer /* inner */ still outer */ x
+            // line comment
+            //// not a doc comment
+            /// Adds one.
+            ///No space.
+            let f = 1 / 2; /* /* unclosed */",
+        );
+
+        let mut lexer = super::Lexer::new(&input);
+
+        let tests = vec![
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Ident, "x"),
+            (super::TokenType::DocComment, "Adds one."),
+            (super::TokenType::DocComment, "No space."),
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Ident, "f"),
+            (super::TokenType::Assign, "="),
+            (super::TokenType::Int, "1"),
+            (super::TokenType::Slash, "/"),
+            (super::TokenType::Int, "2"),
+            (super::TokenType::Semicolon, ";"),
+            (super::TokenType::Eof, ""),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            if tok.token_type != expected.0 || tok.literal != expected.1 {
+                return Err(());
+            }
+        }
+
+        let errors = lexer.errors();
+        if errors.len() != 1
+            || errors[0].kind != super::LexErrorKind::UnterminatedComment
+            || errors[0].span.start.line != 6
+            || errors[0].span.start.column != 28
+        {
+            println!("unexpected errors: {:?}", errors);
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn iterator() -> Result<(), ()> {
+        let lexer = super::Lexer::new("let x = 5; // done");
+
+        let types: Vec<super::TokenType> = lexer.map(|tok| tok.token_type).collect();
+        let expected = vec![
+            super::TokenType::Let,
+            super::TokenType::Ident,
+            super::TokenType::Assign,
+            super::TokenType::Int,
+            super::TokenType::Semicolon,
+        ];
+
+        if types != expected {
+            println!("expected: {:?}, got: {:?}", expected, types);
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn literals_borrow_from_input() -> Result<(), ()> {
+        let input = String::from("let name = \"plain\" + \"esc\\n\"; /// doc");
+
+        let lexer = super::Lexer::new(&input);
+
+        for tok in lexer {
+            let borrowed = matches!(tok.literal, Cow::Borrowed(_));
+            let escaped = tok.literal == "esc\n";
+            println!("got: {:?}", tok);
+
+            if borrowed == escaped {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn trivia_tokens() -> Result<(), ()> {
+        let input = "let x /* a /* b */ */ = 1; // one\n\t/// doc\n";
+
+        let mut lexer = super::Lexer::new(input);
+        lexer.trivia = true;
+
+        let tests = [
+            (super::TokenType::Let, "let"),
+            (super::TokenType::Whitespace, " "),
+            (super::TokenType::Ident, "x"),
+            (super::TokenType::Whitespace, " "),
+            (super::TokenType::Comment, "/* a /* b */ */"),
+            (super::TokenType::Whitespace, " "),
+            (super::TokenType::Assign, "="),
+            (super::TokenType::Whitespace, " "),
+            (super::TokenType::Int, "1"),
+            (super::TokenType::Semicolon, ";"),
+            (super::TokenType::Whitespace, " "),
+            (super::TokenType::Comment, "// one"),
+            (super::TokenType::Whitespace, "\n\t"),
+            (super::TokenType::DocComment, "doc"),
+            (super::TokenType::Whitespace, "\n"),
+            (super::TokenType::Eof, ""),
+        ];
+
+        let mut end = 0;
+
+        for (expected_type, expected_literal) in tests {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected_type, tok);
+
+            if tok.token_type != expected_type || tok.literal != expected_literal {
+                return Err(());
+            }
+
+            // Nothing between tokens is skipped.
+            if tok.span.start.offset != end {
+                return Err(());
+            }
+            end = tok.span.end.offset;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn string_interpolation() -> Result<(), ()> {
+        let input = String::from(
+            r#""hello ${name}, you have ${len(items)} items" "${ {a: "${b}"} }" "${x}" "a ${"#,
+        );
+
+        let mut lexer = super::Lexer::new(&input);
+
+        let tests = vec![
+            (super::TokenType::TemplateHead, "hello "),
+            (super::TokenType::Ident, "name"),
+            (super::TokenType::TemplateMiddle, ", you have "),
+            (super::TokenType::Ident, "len"),
+            (super::TokenType::LParen, "("),
+            (super::TokenType::Ident, "items"),
+            (super::TokenType::RParen, ")"),
+            (super::TokenType::TemplateTail, " items"),
+            (super::TokenType::TemplateHead, ""),
+            (super::TokenType::LBrace, "{"),
+            (super::TokenType::Ident, "a"),
+            (super::TokenType::Colon, ":"),
+            (super::TokenType::TemplateHead, ""),
+            (super::TokenType::Ident, "b"),
+            (super::TokenType::TemplateTail, ""),
+            (super::TokenType::RBrace, "}"),
+            (super::TokenType::TemplateTail, ""),
+            (super::TokenType::TemplateHead, ""),
+            (super::TokenType::Ident, "x"),
+            (super::TokenType::TemplateTail, ""),
+            (super::TokenType::TemplateHead, "a "),
+            (super::TokenType::Eof, ""),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            if tok.token_type != expected.0 || tok.literal != expected.1 {
+                return Err(());
+            }
+        }
+
+        // The last string is still open when the input runs out.
+        match lexer.errors().last() {
+            Some(error)
+                if error.kind == super::LexErrorKind::UnterminatedString
+                    && error.span.start.column == 73 => {}
+            other => {
+                println!("expected an unterminated string at 1:73, got: {:?}", other);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn raw_strings() -> Result<(), ()> {
+        let tests = vec![
+            (r#"r"C:\path\n""#, r"C:\path\n"),
+            (r##"r#"say "hi""#"##, r#"say "hi""#),
+            (
+                r###"r##"contains "# inside"##"###,
+                r##"contains "# inside"##,
+            ),
+            (r##"r#""#"##, ""),
+            ("r\"two\nlines\"", "two\nlines"),
+            (r#"r"${not} interpolated""#, "${not} interpolated"),
+        ];
+
+        for (input, expected) in tests {
+            let mut lexer = super::Lexer::new(input);
+            let tok = lexer.next_token();
+            println!("input: {}, got: {:?}", input, tok);
+
+            if tok.token_type != super::TokenType::String || tok.literal != expected {
+                return Err(());
+            }
+
+            if lexer.next_token().token_type != super::TokenType::Eof || !lexer.errors().is_empty()
+            {
+                return Err(());
+            }
+        }
+
+        let mut lexer = super::Lexer::new(r###"r x r##"open"# + 1"###);
+        let tests = [
+            (super::TokenType::Ident, "r"),
+            (super::TokenType::Ident, "x"),
+            (super::TokenType::Illegal, r##"open"# + 1"##),
+            (super::TokenType::Eof, ""),
+        ];
+
+        for expected in tests.iter() {
+            let tok = lexer.next_token();
+            println!("expected: {:?}, got: {:?}", expected, tok);
+            if tok.token_type != expected.0 || tok.literal != expected.1 {
+                return Err(());
+            }
+        }
+
+        if lexer.errors().len() != 1
+            || lexer.errors()[0].kind != super::LexErrorKind::UnterminatedString
+        {
+            println!("unexpected errors: {:?}", lexer.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn multiline_strings() -> Result<(), ()> {
+        let tests = vec![
+            ("\"\"\"one line\"\"\"", "one line"),
+            (
+                "\"\"\"\n    SELECT *\n      FROM t\n\n    WHERE a = \"b\"\n    \"\"\"",
+                "SELECT *\n  FROM t\n\nWHERE a = \"b\"",
+            ),
+            (
+                "\"\"\"\n\t\t{\n\t\t  \"k\": \"\\u{e9}\\t\"\n\t\t}\n\t\"\"\"",
+                "{\n  \"k\": \"\u{e9}\t\"\n}",
+            ),
+            ("\"\"\"\n  a\\n  b\n  c\"\"\"", "a\n  b\nc"),
+            (
+                "\"\"\"  keeps ${x} \\\"\"\" quotes\"\"\"",
+                "keeps ${x} \"\"\" quotes",
+            ),
+            ("\"\"\"\n\"\"\"", ""),
+            (
+                "\"\"\"\r\n    SELECT *\r\n      FROM t\r\n\r\n    WHERE a\r\n    \"\"\"",
+                "SELECT *\n  FROM t\n\nWHERE a",
+            ),
+            ("\"\"\"\r\n  a\\r\r\n  b\"\"\"", "a\r\nb"),
+        ];
+
+        for (input, expected) in tests {
+            let mut lexer = super::Lexer::new(input);
+            let tok = lexer.next_token();
+            println!("input: {:?}, got: {:?}", input, tok);
+
+            if tok.token_type != super::TokenType::String || tok.literal != expected {
+                return Err(());
+            }
+
+            if lexer.next_token().token_type != super::TokenType::Eof || !lexer.errors().is_empty()
+            {
+                return Err(());
+            }
+        }
+
+        let mut lexer = super::Lexer::new("\"\"\"\n  never \"\" closed");
+        let tok = lexer.next_token();
+        if tok.token_type != super::TokenType::Illegal
+            || lexer.errors().len() != 1
+            || lexer.errors()[0].kind != super::LexErrorKind::UnterminatedString
+        {
+            println!("got: {:?} {:?}", tok, lexer.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,13 @@
+pub mod ast;
+// mod ast_old;
+pub mod builtins;
+pub mod cst;
+pub mod evaluator;
+pub mod lexer;
+pub mod object;
+pub mod parser;
+// mod parser_old;
+pub mod repl;
+pub mod span;
+pub mod stream;
+pub mod tokens;
@@ -1,10 +1,4 @@
-mod ast;
-// mod ast_old;
-mod lexer;
-mod parser;
-// mod parser_old;
-mod repl;
-mod tokens;
+use interpreter::repl;
 
 fn main() {
     repl::Repl::start();
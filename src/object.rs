@@ -0,0 +1,175 @@
+use crate::ast::{BlockStatement, Identifier, Node};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// A runtime value produced by the evaluator.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Object {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+    Char(char),
+    Array(Vec<Object>),
+    /// Key/value pairs in the order the keys were first added.
+    Hash(Vec<(Object, Object)>),
+    Function(Rc<Function>),
+    Builtin(Builtin),
+    Null,
+}
+
+impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "INTEGER",
+            Object::Float(_) => "FLOAT",
+            Object::Boolean(_) => "BOOLEAN",
+            Object::String(_) => "STRING",
+            Object::Char(_) => "CHAR",
+            Object::Array(_) => "ARRAY",
+            Object::Hash(_) => "HASH",
+            Object::Function(_) => "FUNCTION",
+            Object::Builtin(_) => "BUILTIN",
+            Object::Null => "NULL",
+        }
+    }
+
+    /// Whether the value can be used as a hash key.
+    pub fn is_hashable(&self) -> bool {
+        matches!(
+            self,
+            Object::Integer(_) | Object::Boolean(_) | Object::String(_) | Object::Char(_)
+        )
+    }
+
+    /// Everything except `false` and null counts as true in a condition.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Object::Boolean(false) | Object::Null)
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            Object::Float(value) => write!(f, "{:?}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Char(value) => write!(f, "{}", value),
+            Object::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Hash(pairs) => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect();
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            Object::Function(function) => write!(f, "{}", function),
+            Object::Builtin(builtin) => write!(f, "builtin {}", builtin.name),
+            Object::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// A function literal, along with the environment it was evaluated in.
+pub struct Function {
+    pub parameters: Vec<Identifier>,
+    pub body: BlockStatement,
+    pub env: Env,
+}
+
+/// Two functions are only equal if they are the same value.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Function({})", self)
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parameters: Vec<String> = self.parameters.iter().map(|p| p.string()).collect();
+
+        write!(f, "fn({}) {}", parameters.join(", "), self.body.string())
+    }
+}
+
+/// A function implemented in Rust rather than in the language itself.
+#[derive(Clone, Copy)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub func: fn(&[Object]) -> Result<Object, String>,
+}
+
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Builtin({})", self.name)
+    }
+}
+
+/// An environment shared between the code running in it and the functions
+/// that closed over it.
+pub type Env = Rc<RefCell<Environment>>;
+
+/// The variables visible to the code being evaluated.
+#[derive(Default)]
+pub struct Environment {
+    store: HashMap<String, Object>,
+    outer: Option<Env>,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::default()
+    }
+
+    /// A new scope inside `outer`, as for the body of a function call.
+    pub fn enclosed(outer: Env) -> Environment {
+        Environment {
+            store: HashMap::new(),
+            outer: Some(outer),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Object> {
+        match self.store.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.outer.as_ref()?.borrow().get(name),
+        }
+    }
+
+    /// Defines `name` in this scope, shadowing any outer one.
+    pub fn set(&mut self, name: String, value: Object) {
+        self.store.insert(name, value);
+    }
+
+    /// Updates `name` in the innermost scope that defines it, or defines it
+    /// here if none does.
+    pub fn assign(&mut self, name: String, value: Object) {
+        if !self.store.contains_key(&name) {
+            if let Some(outer) = &self.outer {
+                if outer.borrow().get(&name).is_some() {
+                    return outer.borrow_mut().assign(name, value);
+                }
+            }
+        }
+
+        self.store.insert(name, value);
+    }
+}
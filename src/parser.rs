@@ -1,40 +1,110 @@
 use crate::ast::{
-    Expression, ExpressionStatement, Identifier, IntegerLiteral, LetStatement, Program,
-    ReturnStatement, Statement,
+    Alternative, ArrayLiteral, AssignExpression, BlockStatement, Boolean, BreakStatement,
+    CallExpression, CharLiteral, ContinueStatement, Expression, ExpressionStatement, FloatLiteral,
+    ForStatement, FunctionLiteral, HashLiteral, Identifier, IfExpression, IndexExpression,
+    InfixExpression, IntegerLiteral, InterpolatedString, InterpolationPart, LetStatement, Node,
+    PrefixExpression, Program, ReturnStatement, Statement, StringLiteral, WhileStatement,
 };
+use crate::cst::NodeKind;
 use crate::lexer::Lexer;
 use crate::tokens::{Token, TokenType};
 use std::collections::HashMap;
+use std::num::{IntErrorKind, ParseFloatError, ParseIntError};
+use std::ops::Range;
 
-type PrefixParseFn = fn(&mut Parser) -> Option<Expression>;
-type InfixParseFn = fn(Expression) -> Expression;
+type PrefixParseFn<'src> = fn(&mut Parser<'src>) -> Option<Expression>;
+type InfixParseFn<'src> = fn(&mut Parser<'src>, Expression) -> Option<Expression>;
 
 // Precedence constants
 const LOWEST: u8 = 1;
-const EQUALS: u8 = 2; // ==
-const LESS_GREATER: u8 = 3; // > or <
-const SUM: u8 = 4; // +
-const PRODUCT: u8 = 5; // *
-const PREFIX: u8 = 6; // -X or !X
-const CALL: u8 = 7; // my_function(X)
-
-pub struct Parser {
-    pub lexer: Lexer,
-    pub cur_token: Token,
-    pub peek_token: Token,
+const ASSIGN: u8 = 2; // += -= *= /=
+const LOGICAL_OR: u8 = 3; // ||
+const LOGICAL_AND: u8 = 4; // &&
+const EQUALS: u8 = 5; // ==
+const LESS_GREATER: u8 = 6; // > or < or <= or >=
+const SUM: u8 = 7; // +
+const PRODUCT: u8 = 8; // * or / or %
+const PREFIX: u8 = 9; // -X or !X
+const POWER: u8 = 10; // ** binds tighter than prefix: -a ** b == -(a ** b)
+const CALL: u8 = 11; // my_function(X)
+const INDEX: u8 = 12; // array[index]
+
+fn precedence(token_type: &TokenType) -> u8 {
+    match token_type {
+        TokenType::PlusAssign
+        | TokenType::MinusAssign
+        | TokenType::AsteriskAssign
+        | TokenType::SlashAssign => ASSIGN,
+        TokenType::Or => LOGICAL_OR,
+        TokenType::And => LOGICAL_AND,
+        TokenType::Equal | TokenType::BangEqual => EQUALS,
+        TokenType::LessThan
+        | TokenType::GreaterThan
+        | TokenType::LessEqual
+        | TokenType::GreaterEqual => LESS_GREATER,
+        TokenType::Plus | TokenType::Minus => SUM,
+        TokenType::Asterisk | TokenType::Slash | TokenType::Percent => PRODUCT,
+        TokenType::Power => POWER,
+        TokenType::LParen => CALL,
+        TokenType::LBracket => INDEX,
+        _ => LOWEST,
+    }
+}
+
+/// The value of an integer literal as written, with any radix prefix and `_`
+/// separators.
+pub fn integer_value(literal: &str) -> Result<i64, ParseIntError> {
+    let literal = literal.replace('_', "");
+
+    let (digits, radix) = match literal.get(..2) {
+        Some("0x" | "0X") => (&literal[2..], 16),
+        Some("0o" | "0O") => (&literal[2..], 8),
+        Some("0b" | "0B") => (&literal[2..], 2),
+        _ => (literal.as_str(), 10),
+    };
+
+    i64::from_str_radix(digits, radix)
+}
+
+/// The value of a float literal as written, with any `_` separators. Values
+/// too large for an `f64` come out as infinity.
+pub fn float_value(literal: &str) -> Result<f64, ParseFloatError> {
+    literal.replace('_', "").parse()
+}
+
+pub struct Parser<'src> {
+    pub lexer: Lexer<'src>,
+    pub cur_token: Token<'src>,
+    pub peek_token: Token<'src>,
     pub errors: Vec<String>,
 
-    pub prefix_parse_fns: HashMap<TokenType, PrefixParseFn>,
-    pub infix_parse_fns: HashMap<TokenType, InfixParseFn>,
+    /// Doc comments written directly above `cur_token` / `peek_token`.
+    pub cur_doc: Option<String>,
+    pub peek_doc: Option<String>,
+
+    /// When set, every expression read is recorded here, innermost first,
+    /// with the bytes it covers, so a syntax tree can be built around it.
+    pub nodes: Option<Vec<(NodeKind, Range<usize>)>>,
+
+    /// How many loops enclose `cur_token` within the current function, so
+    /// `break` and `continue` can be rejected outside of one.
+    pub loop_depth: usize,
+
+    pub prefix_parse_fns: HashMap<TokenType, PrefixParseFn<'src>>,
+    pub infix_parse_fns: HashMap<TokenType, InfixParseFn<'src>>,
 }
 
-impl Parser {
-    pub fn new(lexer: Lexer) -> Parser {
+impl<'src> Parser<'src> {
+    pub fn new(lexer: Lexer<'src>) -> Parser<'src> {
         let mut parser = Parser {
             lexer,
             cur_token: Token::new(TokenType::Illegal, "".into()),
             peek_token: Token::new(TokenType::Illegal, "".into()),
             errors: Vec::new(),
+            cur_doc: None,
+            peek_doc: None,
+            nodes: None,
+            loop_depth: 0,
             prefix_parse_fns: HashMap::new(),
             infix_parse_fns: HashMap::new(),
         };
@@ -42,287 +112,1055 @@ impl Parser {
         parser.next_token();
         parser.register_prefix(TokenType::Ident, Parser::parse_identifier);
         parser.register_prefix(TokenType::Int, Parser::parse_integer_literal);
+        parser.register_prefix(TokenType::Float, Parser::parse_float_literal);
+        parser.register_prefix(TokenType::String, Parser::parse_string_literal);
+        parser.register_prefix(TokenType::Char, Parser::parse_char_literal);
+        parser.register_prefix(TokenType::TemplateHead, Parser::parse_interpolated_string);
+        parser.register_prefix(TokenType::True, Parser::parse_boolean);
+        parser.register_prefix(TokenType::False, Parser::parse_boolean);
+        parser.register_prefix(TokenType::LParen, Parser::parse_grouped_expression);
+        parser.register_prefix(TokenType::If, Parser::parse_if_expression);
+        parser.register_prefix(TokenType::Function, Parser::parse_function_literal);
+        parser.register_prefix(TokenType::LBracket, Parser::parse_array_literal);
+        parser.register_prefix(TokenType::LBrace, Parser::parse_hash_literal);
+        parser.register_prefix(TokenType::Bang, Parser::parse_prefix_expression);
+        parser.register_prefix(TokenType::Minus, Parser::parse_prefix_expression);
+
+        for token_type in [
+            TokenType::Plus,
+            TokenType::Minus,
+            TokenType::Asterisk,
+            TokenType::Slash,
+            TokenType::Percent,
+            TokenType::Power,
+            TokenType::Equal,
+            TokenType::BangEqual,
+            TokenType::LessThan,
+            TokenType::GreaterThan,
+            TokenType::LessEqual,
+            TokenType::GreaterEqual,
+            TokenType::And,
+            TokenType::Or,
+        ] {
+            parser.register_infix(token_type, Parser::parse_infix_expression);
+        }
+
+        for token_type in [
+            TokenType::PlusAssign,
+            TokenType::MinusAssign,
+            TokenType::AsteriskAssign,
+            TokenType::SlashAssign,
+        ] {
+            parser.register_infix(token_type, Parser::parse_assign_expression);
+        }
+
+        parser.register_infix(TokenType::LParen, Parser::parse_call_expression);
+        parser.register_infix(TokenType::LBracket, Parser::parse_index_expression);
+
         parser
     }
 
     fn parse_identifier(&mut self) -> Option<Expression> {
         Some(Expression::Identifier(Identifier {
-            token: self.cur_token.clone(),
-            value: self.cur_token.literal.clone(),
+            token: self.cur_token.clone().into_owned(),
+            value: self.cur_token.literal.to_string(),
         }))
     }
 
     fn parse_integer_literal(&mut self) -> Option<Expression> {
-        let is_int_literal = self.cur_token.literal.parse::<i64>();
-        let value = match is_int_literal {
+        let value = match integer_value(&self.cur_token.literal) {
             Ok(n) => n,
-            Err(_) => {
-                let msg = format!("could not parse {} as integer", self.cur_token.literal);
+            Err(err) => {
+                let msg = match err.kind() {
+                    IntErrorKind::PosOverflow => format!(
+                        "{}: integer literal {} is out of range",
+                        self.cur_token.span, self.cur_token.literal
+                    ),
+                    _ => format!(
+                        "{}: could not parse {} as integer",
+                        self.cur_token.span, self.cur_token.literal
+                    ),
+                };
                 self.errors.push(msg);
                 return None;
             }
         };
 
         Some(Expression::IntegerLiteral(IntegerLiteral {
-            token: self.cur_token.clone(),
+            token: self.cur_token.clone().into_owned(),
             value,
         }))
     }
 
-    fn no_prefix_parse_fn_error(&mut self, t: TokenType) {
-        let msg = format!("no prefix parse function for {:?} found", t);
-        self.errors.push(msg);
-    }
-
-    fn parse_expression(&mut self, precedence: u8) -> Option<Expression> {
-        let prefix = match self.prefix_parse_fns.get(&self.cur_token.token_type) {
-            Some(pref) => pref,
-            None => return None,
+    fn parse_float_literal(&mut self) -> Option<Expression> {
+        let value = match float_value(&self.cur_token.literal) {
+            Ok(n) if n.is_finite() => n,
+            Ok(_) => {
+                let msg = format!(
+                    "{}: float literal {} is out of range",
+                    self.cur_token.span, self.cur_token.literal
+                );
+                self.errors.push(msg);
+                return None;
+            }
+            Err(_) => {
+                let msg = format!(
+                    "{}: could not parse {} as float",
+                    self.cur_token.span, self.cur_token.literal
+                );
+                self.errors.push(msg);
+                return None;
+            }
         };
 
-        let result = prefix(self);
+        Some(Expression::FloatLiteral(FloatLiteral {
+            token: self.cur_token.clone().into_owned(),
+            value,
+        }))
+    }
 
-        if let Some(_pref) = result {
-            let token_type = &self.cur_token.token_type;
-            self.no_prefix_parse_fn_error(token_type.clone());
-            None
-        } else {
-            let left_exp = result;
-            left_exp
-        }
+    fn parse_string_literal(&mut self) -> Option<Expression> {
+        Some(Expression::StringLiteral(StringLiteral {
+            token: self.cur_token.clone().into_owned(),
+            value: self.cur_token.literal.to_string(),
+        }))
     }
 
-    pub fn next_token(&mut self) {
-        self.cur_token = self.peek_token.clone();
-        self.peek_token = self.lexer.next_token();
+    fn parse_char_literal(&mut self) -> Option<Expression> {
+        Some(Expression::CharLiteral(CharLiteral {
+            token: self.cur_token.clone().into_owned(),
+            value: self.cur_token.literal.chars().next()?,
+        }))
     }
 
-    pub fn errors(&mut self) -> &Vec<String> {
-        &self.errors
+    fn parse_boolean(&mut self) -> Option<Expression> {
+        Some(Expression::Boolean(Boolean {
+            token: self.cur_token.clone().into_owned(),
+            value: self.cur_token_is(TokenType::True),
+        }))
     }
 
-    fn peek_error(&mut self, token_type: TokenType) {
-        let msg = format!(
-            "expected next token to be {:?}, got {:?} instead",
-            token_type, self.cur_token.token_type
-        );
+    /// Parses `( ... )`, which only affects how the expression inside groups.
+    fn parse_grouped_expression(&mut self) -> Option<Expression> {
+        self.next_token();
+        let expression = self.parse_expression(LOWEST)?;
 
-        self.errors.push(msg);
+        if !self.expect_peek(TokenType::RParen) {
+            return None;
+        }
+
+        Some(expression)
     }
 
-    pub fn parse_program(&mut self) -> Program {
-        let mut program = Program {
-            statements: Vec::new(),
-        };
+    fn parse_if_expression(&mut self) -> Option<Expression> {
+        Some(Expression::IfExpression(Box::new(self.parse_if()?)))
+    }
 
-        while self.cur_token.token_type != TokenType::Eof {
-            let stmt = self.parse_statement();
+    /// Parses `if (condition) { ... }` with an optional `else { ... }` or
+    /// `else if ...`.
+    fn parse_if(&mut self) -> Option<IfExpression> {
+        let token = self.cur_token.clone().into_owned();
 
-            if let Some(stmt) = stmt {
-                program.statements.push(stmt);
-            }
-            self.next_token();
+        if !self.expect_peek(TokenType::LParen) {
+            return None;
         }
 
-        program
-    }
+        self.next_token();
+        let condition = self.parse_expression(LOWEST)?;
 
-    pub fn parse_statement(&mut self) -> Option<Statement> {
-        match self.cur_token.token_type {
-            TokenType::Let => self.parse_let_statement(),
-            TokenType::Return => self.parse_return_statement(),
-            _ => self.parse_expression_statement(),
+        if !self.expect_peek(TokenType::RParen) || !self.expect_peek(TokenType::LBrace) {
+            return None;
         }
-    }
 
-    fn parse_expression_statement(&mut self) -> Option<Statement> {
-        let expr = self.parse_expression(LOWEST);
-        let stmt = Statement::Expression(ExpressionStatement {
-            token: self.cur_token.clone(),
-            expression: expr,
-        });
+        let consequence = self.parse_block_statement()?;
 
-        if self.peek_token_is(TokenType::Semicolon) {
+        let alternative = if self.peek_token_is(TokenType::Else) {
             self.next_token();
-        }
-
-        Some(stmt)
-    }
 
-    pub fn parse_let_statement(&mut self) -> Option<Statement> {
-        let name = Identifier {
-            token: self.peek_token.clone(),
-            value: self.peek_token.literal.clone(),
+            if self.peek_token_is(TokenType::If) {
+                self.next_token();
+                let start = self.cur_token.span.start.offset;
+                let if_expression = self.parse_if()?;
+                self.record_node(NodeKind::If, start);
+                Some(Alternative::If(Box::new(if_expression)))
+            } else if self.expect_peek(TokenType::LBrace) {
+                Some(Alternative::Block(self.parse_block_statement()?))
+            } else {
+                return None;
+            }
+        } else {
+            None
         };
 
-        let stmt = Statement::Let(LetStatement {
-            token: self.cur_token.clone(),
-            name,
-            value: None,
-        });
+        Some(IfExpression {
+            token,
+            condition: Box::new(condition),
+            consequence,
+            alternative,
+        })
+    }
 
-        if !self.expect_peek(TokenType::Ident) {
+    /// Parses `fn(x, y) { ... }`.
+    fn parse_function_literal(&mut self) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+
+        if !self.expect_peek(TokenType::LParen) {
             return None;
         }
 
-        if !self.expect_peek(TokenType::Assign) {
+        let parameters = self.parse_function_parameters()?;
+
+        if !self.expect_peek(TokenType::LBrace) {
             return None;
         }
 
-        while !self.cur_token_is(TokenType::Semicolon) && !self.cur_token_is(TokenType::Eof) {
+        // `break` in a function body can't reach a loop around the function.
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body = self.parse_block_statement();
+        self.loop_depth = loop_depth;
+        let body = body?;
+
+        Some(Expression::FunctionLiteral(Box::new(FunctionLiteral {
+            token,
+            parameters,
+            body,
+        })))
+    }
+
+    /// Parses the identifiers after `fn(`, up to and including the `)`.
+    fn parse_function_parameters(&mut self) -> Option<Vec<Identifier>> {
+        let mut parameters = Vec::new();
+
+        if self.peek_token_is(TokenType::RParen) {
             self.next_token();
+            return Some(parameters);
         }
 
-        Some(stmt)
+        loop {
+            if !self.expect_peek(TokenType::Ident) {
+                return None;
+            }
+
+            parameters.push(Identifier {
+                token: self.cur_token.clone().into_owned(),
+                value: self.cur_token.literal.to_string(),
+            });
+
+            if !self.peek_token_is(TokenType::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+
+        if !self.expect_peek(TokenType::RParen) {
+            return None;
+        }
+
+        Some(parameters)
     }
 
-    pub fn parse_return_statement(&mut self) -> Option<Statement> {
-        let stmt = Statement::Return(ReturnStatement {
-            token: self.cur_token.clone(),
-            return_value: Some(Expression::Identifier(Identifier {
-                token: self.peek_token.clone(),
-                value: self.peek_token.literal.clone(),
-            })),
-        });
+    /// Parses statements from the current `{` up to its matching `}`.
+    fn parse_block_statement(&mut self) -> Option<BlockStatement> {
+        let token = self.cur_token.clone().into_owned();
+        let mut statements = Vec::new();
 
         self.next_token();
-        while !self.cur_token_is(TokenType::Semicolon) && !self.cur_token_is(TokenType::Eof) {
+
+        while !self.cur_token_is(TokenType::RBrace) {
+            if self.cur_token_is(TokenType::Eof) {
+                let msg = format!(
+                    "{}: expected }} to close block started at {}, got Eof instead",
+                    self.cur_token.span, token.span
+                );
+                self.errors.push(msg);
+                return None;
+            }
+
+            let start = self.cur_token.span.start.offset;
+            let stmt = self.parse_statement();
+            self.record_node(NodeKind::of_statement(stmt.as_ref()), start);
+            statements.extend(stmt);
             self.next_token();
         }
 
-        Some(stmt)
+        self.record_node(NodeKind::Block, token.span.start.offset);
+        Some(BlockStatement { token, statements })
     }
 
-    fn cur_token_is(&mut self, token_type: TokenType) -> bool {
-        self.cur_token.token_type == token_type
-    }
+    /// Parses `"hello ${name}, bye"`, lexed as the tokens `TemplateHead`,
+    /// the tokens of `name`, then `TemplateTail` (or `TemplateMiddle` if
+    /// another `${` follows).
+    fn parse_interpolated_string(&mut self) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+        let mut parts = Vec::new();
+
+        loop {
+            if !self.cur_token.literal.is_empty() {
+                parts.push(InterpolationPart::Literal(
+                    self.cur_token.literal.to_string(),
+                ));
+            }
 
-    fn peek_token_is(&mut self, token_type: TokenType) -> bool {
-        self.peek_token.token_type == token_type
-    }
+            if self.cur_token_is(TokenType::TemplateTail) {
+                break;
+            }
 
-    fn expect_peek(&mut self, token_type: TokenType) -> bool {
-        if self.peek_token_is(token_type.clone()) {
             self.next_token();
-            true
-        } else {
-            self.peek_error(token_type);
-            false
+            let expr = self.parse_expression(LOWEST)?;
+            parts.push(InterpolationPart::Expression(expr));
+
+            if !self.peek_token_is(TokenType::TemplateMiddle)
+                && !self.peek_token_is(TokenType::TemplateTail)
+            {
+                let msg = format!(
+                    "{}: expected }} to close interpolation started at {}, got {:?} instead",
+                    self.peek_token.span, token.span, self.peek_token.token_type
+                );
+                self.errors.push(msg);
+                return None;
+            }
+            self.next_token();
         }
-    }
 
-    fn register_prefix(&mut self, token_type: TokenType, func: PrefixParseFn) {
-        self.prefix_parse_fns.insert(token_type, func);
+        Some(Expression::InterpolatedString(InterpolatedString {
+            token,
+            parts,
+        }))
     }
 
-    fn register_infix(&mut self, token_type: TokenType, func: InfixParseFn) {
-        self.infix_parse_fns.insert(token_type, func);
+    fn no_prefix_parse_fn_error(&mut self) {
+        let msg = format!(
+            "{}: no prefix parse function for {:?} {:?} found",
+            self.cur_token.span, self.cur_token.token_type, self.cur_token.literal
+        );
+        self.errors.push(msg);
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::ast::{
-        Expression, ExpressionStatement, Identifier, IntegerLiteral, LetStatement, Node,
-        PrefixExpression, Program, ReturnStatement, Statement,
-    };
+    fn parse_expression(&mut self, precedence: u8) -> Option<Expression> {
+        let start = self.cur_token.span.start.offset;
+        let recorded = self.nodes.as_ref().map_or(0, Vec::len);
 
-    use super::*;
+        let left = self.parse_expression_from(precedence, start);
 
-    #[test]
-    fn test_let_statements() -> Result<(), ()> {
-        let input = String::from(
-            "let x = 5;
-		let y = 10;
-		let foobar = 838383;",
-        );
+        // Nothing read on the way to a failure ends up in the AST.
+        if left.is_none() {
+            if let Some(nodes) = &mut self.nodes {
+                nodes.truncate(recorded);
+            }
+        }
 
-        let lexer = Lexer::new(input);
+        left
+    }
 
-        let mut parser = Parser::new(lexer);
+    fn parse_expression_from(&mut self, precedence: u8, start: usize) -> Option<Expression> {
+        let prefix = match self.prefix_parse_fns.get(&self.cur_token.token_type) {
+            Some(prefix) => *prefix,
+            None => {
+                self.no_prefix_parse_fn_error();
+                return None;
+            }
+        };
 
-        let program = parser.parse_program();
+        // A group has no node of its own in the AST, only in the CST.
+        let grouped = self.cur_token_is(TokenType::LParen);
+        let mut left = prefix(self)?;
+        let kind = if grouped {
+            NodeKind::Paren
+        } else {
+            NodeKind::of_expression(&left)
+        };
+        self.record_node(kind, start);
 
-        check_parser_errors(&mut parser)?;
+        while !self.peek_token_is(TokenType::Semicolon) && precedence < self.peek_precedence() {
+            let infix = match self.infix_parse_fns.get(&self.peek_token.token_type) {
+                Some(infix) => *infix,
+                None => return Some(left),
+            };
 
-        if program.statements.len() != 3 {
-            println!(
-                "program.statements does not contain 3 statements. got={}",
-                program.statements.len()
-            );
-            return Err(());
+            self.next_token();
+            left = infix(self, left)?;
+            self.record_node(NodeKind::of_expression(&left), start);
         }
 
-        let tests = vec!["x", "y", "foobar"];
+        Some(left)
+    }
 
-        for (i, tt) in tests.iter().enumerate() {
-            let stmt = &program.statements[i];
+    /// Records a node of `kind` running from `start` to the end of the
+    /// current token, if nodes are being recorded.
+    fn record_node(&mut self, kind: NodeKind, start: usize) {
+        let end = self.cur_token.span.end.offset;
 
-            if !test_let_statement(stmt, tt) {
-                return Err(());
-            }
+        if let Some(nodes) = &mut self.nodes {
+            nodes.push((kind, start..end));
         }
+    }
 
-        Ok(())
+    fn parse_prefix_expression(&mut self) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+        self.next_token();
+
+        Some(Expression::PrefixExpression(PrefixExpression {
+            operator: token.literal.to_string(),
+            token,
+            right: Box::new(self.parse_expression(PREFIX)?),
+        }))
     }
 
-    fn test_let_statement(s: &Statement, name: &str) -> bool {
-        if s.token_literal() != "let" {
-            println!("s.token_literal not 'let'. got={}", s.token_literal());
-            return false;
+    fn parse_infix_expression(&mut self, left: Expression) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+        let mut precedence = self.cur_precedence();
+
+        // `**` is right-associative: 2 ** 3 ** 2 == 2 ** (3 ** 2)
+        if token.token_type == TokenType::Power {
+            precedence -= 1;
         }
 
-        let let_stmt = match s {
-            Statement::Let(stmt) => stmt,
-            _ => {
-                println!("s is not LetStatement. got={}", s.token_literal());
-                return false;
-            }
-        };
+        self.next_token();
+        let right = self.parse_expression(precedence)?;
 
-        if let_stmt.name.value != name {
-            println!(
-                "let_stmt.name.value not '{}'. got={}",
-                name, let_stmt.name.value
+        Some(Expression::InfixExpression(InfixExpression {
+            operator: token.literal.to_string(),
+            token,
+            left: Box::new(left),
+            right: Box::new(right),
+        }))
+    }
+
+    fn parse_assign_expression(&mut self, target: Expression) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+
+        if !matches!(target, Expression::Identifier(_)) {
+            let msg = format!(
+                "{}: cannot assign to {} with {}",
+                token.span,
+                target.string(),
+                token.literal
             );
-            return false;
+            self.errors.push(msg);
+            return None;
         }
 
-        true
+        // Assignment is right-associative: a += b += 1 == a += (b += 1)
+        self.next_token();
+        let value = self.parse_expression(ASSIGN - 1)?;
+
+        Some(Expression::AssignExpression(AssignExpression {
+            operator: token.literal.to_string(),
+            token,
+            target: Box::new(target),
+            value: Box::new(value),
+        }))
     }
 
-    #[test]
-    fn test_return_statements() -> Result<(), ()> {
-        let input = String::from(
-            "return 5;
-			return 10;
-			return 993322;",
-        );
+    fn parse_call_expression(&mut self, function: Expression) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+        let arguments = self.parse_expression_list(TokenType::RParen)?;
 
-        let lexer = Lexer::new(input);
-        let mut parser = Parser::new(lexer);
-        let program = parser.parse_program();
+        Some(Expression::CallExpression(CallExpression {
+            token,
+            function: Box::new(function),
+            arguments,
+        }))
+    }
 
-        check_parser_errors(&mut parser)?;
+    fn parse_array_literal(&mut self) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+        let elements = self.parse_expression_list(TokenType::RBracket)?;
 
-        if program.statements.len() != 3 {
-            println!(
-                "program.statements does not contain 3 statements. got={}",
-                program.statements.len()
-            );
-            return Err(());
+        Some(Expression::ArrayLiteral(ArrayLiteral { token, elements }))
+    }
+
+    /// Parses `{key: value, ...}`. A `{` only starts a block after `if`,
+    /// `fn`, `while` or `for`, which parse their bodies themselves, so one reached in
+    /// expression position is a hash if it is empty or its first key is
+    /// followed by `:`. Anything else is a block written where one is not
+    /// allowed.
+    fn parse_hash_literal(&mut self) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+
+        if self.peek_token_is(TokenType::RBrace) {
+            self.next_token();
+            return Some(Expression::HashLiteral(HashLiteral {
+                token,
+                pairs: Vec::new(),
+            }));
         }
 
-        for stmt in &program.statements {
-            if let Statement::Return(return_stmt) = stmt {
-                if return_stmt.token_literal() != "return" {
-                    println!(
-                        "s.token_literal not 'return'. got={}",
-                        return_stmt.token_literal()
-                    );
-                    return Err(());
-                }
-            } else {
+        let starts_statement =
+            self.peek_token_is(TokenType::Let) || self.peek_token_is(TokenType::Return);
+        let key = if starts_statement {
+            None
+        } else {
+            self.next_token();
+            Some(self.parse_expression(LOWEST)?)
+        };
+
+        let key = match key {
+            Some(key) if self.peek_token_is(TokenType::Colon) => key,
+            _ => {
+                let msg = format!(
+                    "{}: blocks are only allowed after if, fn, while or for",
+                    token.span
+                );
+                self.errors.push(msg);
+                self.skip_block();
+                return None;
+            }
+        };
+
+        self.next_token();
+        self.next_token();
+        let mut pairs = vec![(key, self.parse_expression(LOWEST)?)];
+
+        if self.peek_token_is(TokenType::Comma) {
+            self.next_token();
+            pairs.extend(self.parse_list(
+                &token,
+                TokenType::RBrace,
+                "hash",
+                Parser::parse_hash_pair,
+            )?);
+        } else if !self.expect_closing(TokenType::RBrace, "hash", &token) {
+            return None;
+        }
+
+        Some(Expression::HashLiteral(HashLiteral { token, pairs }))
+    }
+
+    /// Moves past the `}` that closes the block the parser is in, so parsing
+    /// picks up after it.
+    fn skip_block(&mut self) {
+        let mut depth = 0;
+
+        while !self.peek_token_is(TokenType::Eof) {
+            self.next_token();
+
+            match self.cur_token.token_type {
+                TokenType::LBrace => depth += 1,
+                TokenType::RBrace if depth == 0 => return,
+                TokenType::RBrace => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_hash_pair(&mut self) -> Option<(Expression, Expression)> {
+        let key = self.parse_expression(LOWEST)?;
+
+        if !self.expect_peek(TokenType::Colon) {
+            return None;
+        }
+
+        self.next_token();
+        let value = self.parse_expression(LOWEST)?;
+
+        Some((key, value))
+    }
+
+    fn parse_index_expression(&mut self, left: Expression) -> Option<Expression> {
+        let token = self.cur_token.clone().into_owned();
+
+        if self.peek_token_is(TokenType::RBracket) {
+            let msg = format!(
+                "{}: expected an index between [ and ], got nothing",
+                self.peek_token.span
+            );
+            self.errors.push(msg);
+            return None;
+        }
+
+        self.next_token();
+        let index = self.parse_expression(LOWEST)?;
+
+        if !self.expect_closing(TokenType::RBracket, "index", &token) {
+            return None;
+        }
+
+        Some(Expression::IndexExpression(IndexExpression {
+            token,
+            left: Box::new(left),
+            index: Box::new(index),
+        }))
+    }
+
+    /// Parses the comma-separated expressions after the current `(` or `[`,
+    /// up to and including `end`.
+    fn parse_expression_list(&mut self, end: TokenType) -> Option<Vec<Expression>> {
+        let what = if end == TokenType::RParen {
+            "call"
+        } else {
+            "list"
+        };
+
+        let open = self.cur_token.clone();
+        self.parse_list(&open, end, what, |parser| parser.parse_expression(LOWEST))
+    }
+
+    /// Parses comma-separated items after the current token, up to and
+    /// including `end`. A trailing comma is allowed. Running out of input is
+    /// reported against `open`, the bracket the list started at.
+    fn parse_list<T>(
+        &mut self,
+        open: &Token,
+        end: TokenType,
+        what: &str,
+        parse_item: fn(&mut Parser<'src>) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let mut list = Vec::new();
+
+        while !self.peek_token_is(end.clone()) {
+            if self.peek_token_is(TokenType::Comma) {
+                let msg = format!(
+                    "{}: expected an expression, got Comma instead",
+                    self.peek_token.span
+                );
+                self.errors.push(msg);
+                return None;
+            }
+
+            if self.peek_token_is(TokenType::Eof) {
+                break;
+            }
+
+            self.next_token();
+            list.push(parse_item(self)?);
+
+            if !self.peek_token_is(TokenType::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+
+        if !self.expect_closing(end, what, open) {
+            return None;
+        }
+
+        Some(list)
+    }
+
+    /// Like `expect_peek`, but running out of input names the bracket that
+    /// was left open.
+    fn expect_closing(&mut self, end: TokenType, what: &str, open: &Token) -> bool {
+        if !self.peek_token_is(TokenType::Eof) {
+            return self.expect_peek(end);
+        }
+
+        let closer = match end {
+            TokenType::RParen => ")",
+            TokenType::RBracket => "]",
+            _ => "}",
+        };
+        let msg = format!(
+            "{}: expected {} to close {} started at {}, got Eof instead",
+            self.peek_token.span, closer, what, open.span
+        );
+        self.errors.push(msg);
+        false
+    }
+
+    fn peek_precedence(&self) -> u8 {
+        precedence(&self.peek_token.token_type)
+    }
+
+    fn cur_precedence(&self) -> u8 {
+        precedence(&self.cur_token.token_type)
+    }
+
+    pub fn next_token(&mut self) {
+        self.cur_token = self.peek_token.clone();
+        self.cur_doc = self.peek_doc.take();
+        self.peek_token = self.lexer.next_token();
+
+        while self.peek_token.token_type == TokenType::DocComment {
+            let doc = self.peek_doc.get_or_insert_with(String::new);
+            if !doc.is_empty() {
+                doc.push('\n');
+            }
+            doc.push_str(&self.peek_token.literal);
+
+            self.peek_token = self.lexer.next_token();
+        }
+    }
+
+    pub fn errors(&mut self) -> &Vec<String> {
+        &self.errors
+    }
+
+    fn peek_error(&mut self, token_type: TokenType) {
+        let msg = format!(
+            "{}: expected next token to be {:?}, got {:?} instead",
+            self.peek_token.span, token_type, self.peek_token.token_type
+        );
+
+        self.errors.push(msg);
+    }
+
+    pub fn parse_program(&mut self) -> Program {
+        let mut program = Program {
+            statements: Vec::new(),
+        };
+
+        while self.cur_token.token_type != TokenType::Eof {
+            let stmt = self.parse_statement();
+
+            if let Some(stmt) = stmt {
+                program.statements.push(stmt);
+            }
+            self.next_token();
+        }
+
+        program
+    }
+
+    pub fn parse_statement(&mut self) -> Option<Statement> {
+        match self.cur_token.token_type {
+            TokenType::Let => self.parse_let_statement(),
+            TokenType::Return => self.parse_return_statement(),
+            TokenType::While => self.parse_while_statement(),
+            TokenType::For => self.parse_for_statement(),
+            TokenType::Break | TokenType::Continue => self.parse_loop_control_statement(),
+            _ => self.parse_expression_statement(),
+        }
+    }
+
+    fn parse_expression_statement(&mut self) -> Option<Statement> {
+        let token = self.cur_token.clone().into_owned();
+        let stmt = Statement::Expression(ExpressionStatement {
+            token,
+            expression: self.parse_expression(LOWEST),
+        });
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(stmt)
+    }
+
+    pub fn parse_let_statement(&mut self) -> Option<Statement> {
+        let token = self.cur_token.clone().into_owned();
+        let doc = self.cur_doc.take();
+
+        if !self.expect_peek(TokenType::Ident) {
+            return None;
+        }
+
+        let name = Identifier {
+            token: self.cur_token.clone().into_owned(),
+            value: self.cur_token.literal.to_string(),
+        };
+
+        if !self.expect_peek(TokenType::Assign) {
+            return None;
+        }
+
+        self.next_token();
+        let value = self.parse_expression(LOWEST)?;
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Statement::Let(LetStatement {
+            token,
+            doc,
+            name,
+            value: Some(value),
+        }))
+    }
+
+    /// Parses `return <expression>;`, or a bare `return;` with no value.
+    pub fn parse_return_statement(&mut self) -> Option<Statement> {
+        let token = self.cur_token.clone().into_owned();
+
+        let return_value = match self.peek_token.token_type {
+            TokenType::Semicolon | TokenType::RBrace | TokenType::Eof => None,
+            _ => {
+                self.next_token();
+                Some(self.parse_expression(LOWEST)?)
+            }
+        };
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Statement::Return(ReturnStatement {
+            token,
+            return_value,
+        }))
+    }
+
+    /// Parses `while (condition) { ... }`.
+    fn parse_while_statement(&mut self) -> Option<Statement> {
+        let token = self.cur_token.clone().into_owned();
+
+        if !self.expect_peek(TokenType::LParen) {
+            return None;
+        }
+
+        self.next_token();
+        let condition = self.parse_expression(LOWEST)?;
+
+        if !self.expect_peek(TokenType::RParen) || !self.expect_peek(TokenType::LBrace) {
+            return None;
+        }
+
+        let body = self.parse_loop_body()?;
+
+        Some(Statement::While(WhileStatement {
+            token,
+            condition,
+            body,
+        }))
+    }
+
+    /// Parses `for (variable in iterable) { ... }`.
+    fn parse_for_statement(&mut self) -> Option<Statement> {
+        let token = self.cur_token.clone().into_owned();
+
+        if !self.expect_peek(TokenType::LParen) || !self.expect_peek(TokenType::Ident) {
+            return None;
+        }
+
+        let variable = Identifier {
+            token: self.cur_token.clone().into_owned(),
+            value: self.cur_token.literal.to_string(),
+        };
+
+        if !self.expect_peek(TokenType::In) {
+            return None;
+        }
+
+        self.next_token();
+        let iterable = self.parse_expression(LOWEST)?;
+
+        if !self.expect_peek(TokenType::RParen) || !self.expect_peek(TokenType::LBrace) {
+            return None;
+        }
+
+        let body = self.parse_loop_body()?;
+
+        Some(Statement::For(ForStatement {
+            token,
+            variable,
+            iterable,
+            body,
+        }))
+    }
+
+    fn parse_loop_body(&mut self) -> Option<BlockStatement> {
+        self.loop_depth += 1;
+        let body = self.parse_block_statement();
+        self.loop_depth -= 1;
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        body
+    }
+
+    /// Parses `break;` or `continue;`, which are only allowed inside a loop.
+    fn parse_loop_control_statement(&mut self) -> Option<Statement> {
+        let token = self.cur_token.clone().into_owned();
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        if self.loop_depth == 0 {
+            let msg = format!("{}: {} outside of a loop", token.span, token.literal);
+            self.errors.push(msg);
+            return None;
+        }
+
+        Some(match token.token_type {
+            TokenType::Break => Statement::Break(BreakStatement { token }),
+            _ => Statement::Continue(ContinueStatement { token }),
+        })
+    }
+
+    fn cur_token_is(&mut self, token_type: TokenType) -> bool {
+        self.cur_token.token_type == token_type
+    }
+
+    fn peek_token_is(&mut self, token_type: TokenType) -> bool {
+        self.peek_token.token_type == token_type
+    }
+
+    fn expect_peek(&mut self, token_type: TokenType) -> bool {
+        if self.peek_token_is(token_type.clone()) {
+            self.next_token();
+            true
+        } else {
+            self.peek_error(token_type);
+            false
+        }
+    }
+
+    fn register_prefix(&mut self, token_type: TokenType, func: PrefixParseFn<'src>) {
+        self.prefix_parse_fns.insert(token_type, func);
+    }
+
+    fn register_infix(&mut self, token_type: TokenType, func: InfixParseFn<'src>) {
+        self.infix_parse_fns.insert(token_type, func);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::{
+        Expression, ExpressionStatement, Identifier, IntegerLiteral, InterpolationPart,
+        LetStatement, Node, PrefixExpression, Program, ReturnStatement, Statement,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_let_statements() -> Result<(), ()> {
+        let input = String::from(
+            "let x = 5;
+		let y = 10;
+		let foobar = 838383;",
+        );
+
+        let lexer = Lexer::new(&input);
+
+        let mut parser = Parser::new(lexer);
+
+        let program = parser.parse_program();
+
+        check_parser_errors(&mut parser)?;
+
+        if program.statements.len() != 3 {
+            println!(
+                "program.statements does not contain 3 statements. got={}",
+                program.statements.len()
+            );
+            return Err(());
+        }
+
+        let tests = vec!["x", "y", "foobar"];
+
+        for (i, tt) in tests.iter().enumerate() {
+            let stmt = &program.statements[i];
+
+            if !test_let_statement(stmt, tt) {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_let_doc_comments() -> Result<(), ()> {
+        let input = String::from(
+            "/// The answer.
+            /// Computed slowly.
+            let answer = 42;
+            let undocumented = 1;
+            /// Dropped: not followed by a let.
+            answer;
+            // ordinary comment
+            /// Adds things.
+            let add = 2;",
+        );
+
+        let lexer = Lexer::new(&input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        let expected = vec![
+            Some("The answer.\nComputed slowly."),
+            None,
+            None,
+            Some("Adds things."),
+        ];
+
+        if program.statements.len() != expected.len() {
+            println!(
+                "program.statements does not contain {} statements. got={}",
+                expected.len(),
+                program.statements.len()
+            );
+            return Err(());
+        }
+
+        for (stmt, doc) in program.statements.iter().zip(expected) {
+            let got = match stmt {
+                Statement::Let(let_stmt) => let_stmt.doc.as_deref(),
+                _ => None,
+            };
+
+            if got != doc {
+                println!("expected doc {:?}, got {:?}", doc, got);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn test_let_statement(s: &Statement, name: &str) -> bool {
+        if s.token_literal() != "let" {
+            println!("s.token_literal not 'let'. got={}", s.token_literal());
+            return false;
+        }
+
+        let let_stmt = match s {
+            Statement::Let(stmt) => stmt,
+            _ => {
+                println!("s is not LetStatement. got={}", s.token_literal());
+                return false;
+            }
+        };
+
+        if let_stmt.name.value != name {
+            println!(
+                "let_stmt.name.value not '{}'. got={}",
+                name, let_stmt.name.value
+            );
+            return false;
+        }
+
+        true
+    }
+
+    #[test]
+    fn test_return_statements() -> Result<(), ()> {
+        let input = String::from(
+            "return 5;
+			return 10;
+			return 993322;",
+        );
+
+        let lexer = Lexer::new(&input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+
+        check_parser_errors(&mut parser)?;
+
+        if program.statements.len() != 3 {
+            println!(
+                "program.statements does not contain 3 statements. got={}",
+                program.statements.len()
+            );
+            return Err(());
+        }
+
+        for stmt in &program.statements {
+            if let Statement::Return(return_stmt) = stmt {
+                if return_stmt.token_literal() != "return" {
+                    println!(
+                        "s.token_literal not 'return'. got={}",
+                        return_stmt.token_literal()
+                    );
+                    return Err(());
+                }
+            } else {
                 println!("stmt not ReturnStatement. got={}", stmt.token_literal());
                 return Err(());
             }
@@ -332,57 +1170,762 @@ mod test {
     }
 
     #[test]
-    fn test_identifier_expression() -> Result<(), ()> {
-        let input = String::from("foobar");
+    fn test_statement_values() -> Result<(), ()> {
+        let tests = [
+            ("let x = 5;", "let x = 5;"),
+            ("let sum = a + b * 2;", "let sum = (a + (b * 2));"),
+            ("let neg = -x", "let neg = (-x);"),
+            ("return 5 + 5;", "return (5 + 5);"),
+            ("return x", "return x;"),
+            ("return;", "return;"),
+            ("let a = 1; return a;", "let a = 1;return a;"),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
+                return Err(());
+            }
+        }
+
+        let mut parser = Parser::new(Lexer::new("let x = ;"));
+        parser.parse_program();
+
+        let expected = vec![r#"1:9: no prefix parse function for Semicolon ";" found"#];
+        if parser.errors() != &expected {
+            println!("unexpected errors: {:?}", parser.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_identifier_expression() -> Result<(), ()> {
+        let input = String::from("foobar");
+
+        let lexer = Lexer::new(&input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        if program.statements.len() != 1 {
+            println!(
+                "Program doesn't have enough statements. Got {}",
+                program.statements.len()
+            );
+            return Err(());
+        }
+
+        let stmt = match &program.statements[0] {
+            Statement::Expression(expr_stmt) => expr_stmt,
+            _ => {
+                println!("Statement not an ExpressionStatement");
+                return Err(());
+            }
+        };
+
+        let expr = match &stmt.expression {
+            Some(e) => e,
+            None => {
+                println!("Statement not an ExpressionStatement");
+                return Err(());
+            }
+        };
+
+        let ident = match expr {
+            Expression::Identifier(ident) => ident,
+            _ => {
+                println!("Expression not an Identifier");
+                return Err(());
+            }
+        };
+
+        if ident.value != String::from("foobar") {
+            println!("ident.value is not {}. Got {}", "foobar", ident.value);
+            return Err(());
+        }
+
+        if ident.token_literal() != String::from("foobar") {
+            println!(
+                "ident.token_literal is not {}. Got: {}",
+                "foobar",
+                ident.token_literal()
+            );
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_integer_literal_expression() -> Result<(), ()> {
+        let input = String::from("5;");
+        let lexer = Lexer::new(&input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        if program.statements.len() != 1 {
+            println!(
+                "program doesn't have enough statements, got: {}",
+                program.statements.len()
+            );
+            return Err(());
+        }
+
+        let stmt = match &program.statements[0] {
+            Statement::Expression(expr_stmt) => expr_stmt,
+            _ => {
+                println!("Statement is not an ExpressionStatement");
+                return Err(());
+            }
+        };
+
+        let stmt_expr = match &stmt.expression {
+            Some(e) => e,
+            None => {
+                println!("Statment not expression");
+                return Err(());
+            }
+        };
+
+        let expr = match stmt_expr {
+            Expression::IntegerLiteral(literal) => literal,
+            _ => {
+                println!("Something went wrong");
+                return Err(());
+            }
+        };
+
+        if expr.value != 5 {
+            println!("Expected literal value to be {} but got {}", 5, expr.value);
+        }
+
+        if expr.token_literal() != "5".to_owned() {
+            println!(
+                "Expected token literal to be {} but got {}",
+                "5",
+                expr.token_literal()
+            );
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefix_operators() -> Result<(), ()> {
+        struct PrefixTests {
+            input: String,
+            operator: String,
+            integer_value: i64,
+        }
+
+        let prefix_tests = vec![
+            PrefixTests {
+                input: String::from("!5;"),
+                operator: String::from("!"),
+                integer_value: 5,
+            },
+            PrefixTests {
+                input: String::from("-15;"),
+                operator: String::from("-"),
+                integer_value: 15,
+            },
+        ];
+
+        for tt in prefix_tests.iter() {
+            let lexer = Lexer::new(&tt.input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.statements.len() != 1 {
+                println!(
+                    "program does not have enough statements. got {}",
+                    program.statements.len()
+                );
+                return Err(());
+            }
+
+            let stmt = match &program.statements[0] {
+                Statement::Expression(expr_stmt) => expr_stmt,
+                _ => {
+                    println!("Statement is not an ExpressionStatement");
+                    return Err(());
+                }
+            };
+
+            let stmt_expr = match &stmt.expression {
+                Some(e) => e,
+                None => {
+                    println!("Statment not expression");
+                    return Err(());
+                }
+            };
+
+            let expr = match stmt_expr {
+                Expression::PrefixExpression(prefix_expr) => prefix_expr,
+                _ => {
+                    println!("Something went wrong");
+                    return Err(());
+                }
+            };
+
+            if expr.operator != tt.operator {
+                println!(
+                    "Expressions operator is not {}. got {}",
+                    tt.operator, expr.operator
+                );
+                return Err(());
+            }
+
+            let right = match *expr.right {
+                Expression::IntegerLiteral(ref literal) => literal.clone(),
+                _ => return Err(()),
+            };
+
+            if !test_integer_literal(&right, tt.integer_value) {
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_prefix_parse_fn_error() -> Result<(), ()> {
+        let lexer = Lexer::new("a;\n  * 2");
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        let expected = vec![r#"2:3: no prefix parse function for Asterisk "*" found"#];
+        if parser.errors() != &expected {
+            println!("unexpected errors: {:?}", parser.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_boolean_expression() -> Result<(), ()> {
+        for (input, expected) in [("true;", true), ("false;", false)] {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            match &program.statements[..] {
+                [Statement::Expression(ExpressionStatement {
+                    expression: Some(Expression::Boolean(boolean)),
+                    ..
+                })] if boolean.value == expected => {}
+                _ => {
+                    println!("{} did not parse to the boolean {}", input, expected);
+                    return Err(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unclosed_group() -> Result<(), ()> {
+        let mut parser = Parser::new(Lexer::new("(1 + 2;"));
+        parser.parse_program();
+
+        let expected = "1:7: expected next token to be RParen, got Semicolon instead";
+        if parser.errors().first().map(String::as_str) != Some(expected) {
+            println!("unexpected errors: {:?}", parser.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
 
-        let lexer = Lexer::new(input);
+    #[test]
+    fn test_if_expression() -> Result<(), ()> {
+        let lexer = Lexer::new("if (x < y) { x }");
         let mut parser = Parser::new(lexer);
         let program = parser.parse_program();
         check_parser_errors(&mut parser)?;
 
-        if program.statements.len() != 1 {
-            println!(
-                "Program doesn't have enough statements. Got {}",
-                program.statements.len()
-            );
+        let expr = match &program.statements[..] {
+            [Statement::Expression(ExpressionStatement {
+                expression: Some(Expression::IfExpression(expr)),
+                ..
+            })] => expr,
+            _ => {
+                println!("program is not a single IfExpression");
+                return Err(());
+            }
+        };
+
+        if expr.condition.string() != "(x < y)" {
+            println!("condition is not (x < y). got {}", expr.condition.string());
             return Err(());
         }
 
-        let stmt = match &program.statements[0] {
-            Statement::Expression(expr_stmt) => expr_stmt,
+        match &expr.consequence.statements[..] {
+            [Statement::Expression(ExpressionStatement {
+                expression: Some(Expression::Identifier(ident)),
+                ..
+            })] if ident.value == "x" => {}
             _ => {
-                println!("Statement not an ExpressionStatement");
+                println!("consequence is not x. got {}", expr.consequence.string());
+                return Err(());
+            }
+        }
+
+        if expr.alternative.is_some() {
+            println!("alternative was not None");
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_if_else_expressions() -> Result<(), ()> {
+        let tests = [
+            (
+                "if (x < y) { x } else { y }",
+                "if ((x < y)) { x } else { y }",
+            ),
+            ("if (x) {}", "if (x) {}"),
+            (
+                "if (a) { 1 } else if (b) { 2 } else { 3 }",
+                "if (a) { 1 } else if (b) { 2 } else { 3 }",
+            ),
+            (
+                "if (a) { 1 } else if (b) { 2 } else if (c) { 3 }",
+                "if (a) { 1 } else if (b) { 2 } else if (c) { 3 }",
+            ),
+            (
+                "if (x) { let y = x * 2; return y; } else { if (!x) { 0 } }",
+                "if (x) { let y = (x * 2);return y; } else { if ((!x)) { 0 } }",
+            ),
+            (
+                "let max = if (a > b) { a } else { b };",
+                "let max = if ((a > b)) { a } else { b };",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_if_expression_errors() -> Result<(), ()> {
+        let tests = [
+            (
+                "if (x) {\n  let y = 1;\n",
+                "3:1: expected } to close block started at 1:8, got Eof instead",
+            ),
+            (
+                "if (x) { 1 } else { if (y) { 2 }",
+                "1:33: expected } to close block started at 1:19, got Eof instead",
+            ),
+            (
+                "if x { 1 }",
+                "1:4: expected next token to be LParen, got Ident instead",
+            ),
+            (
+                "if (x) { 1 } else 2",
+                "1:19: expected next token to be LBrace, got Int instead",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+
+            if parser.errors().first().map(String::as_str) != Some(expected) {
+                println!("unexpected errors: {:?}", parser.errors());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_while_statement() -> Result<(), ()> {
+        let lexer = Lexer::new("while (x < 10) { x += 1; }");
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        let stmt = match &program.statements[..] {
+            [Statement::While(stmt)] => stmt,
+            _ => {
+                println!("program is not a single WhileStatement");
                 return Err(());
             }
         };
 
-        let expr = match &stmt.expression {
-            Some(e) => e,
-            None => {
-                println!("Statement not an ExpressionStatement");
+        if stmt.condition.string() != "(x < 10)" {
+            println!("condition is not (x < 10). got {}", stmt.condition.string());
+            return Err(());
+        }
+
+        if stmt.body.string() != "{ (x += 1) }" {
+            println!("body is not {{ (x += 1) }}. got {}", stmt.body.string());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_for_statement() -> Result<(), ()> {
+        let lexer = Lexer::new("for (item in [1, 2]) { print(item); }");
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        let stmt = match &program.statements[..] {
+            [Statement::For(stmt)] => stmt,
+            _ => {
+                println!("program is not a single ForStatement");
+                return Err(());
+            }
+        };
+
+        if stmt.variable.value != "item" {
+            println!("variable is not item. got {}", stmt.variable.value);
+            return Err(());
+        }
+
+        if stmt.iterable.string() != "[1, 2]" {
+            println!("iterable is not [1, 2]. got {}", stmt.iterable.string());
+            return Err(());
+        }
+
+        if stmt.body.string() != "{ print(item) }" {
+            println!("body is not {{ print(item) }}. got {}", stmt.body.string());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_loop_statements() -> Result<(), ()> {
+        let tests = [
+            ("while (true) { break; }", "while (true) { break; }"),
+            ("while (x) { continue }", "while (x) { continue; }"),
+            (
+                "for (k in {\"a\": 1}) { if (k == \"a\") { break; } }",
+                "for (k in {\"a\": 1}) { if ((k == \"a\")) { break; } }",
+            ),
+            (
+                "for (c in \"abc\") { while (c) { continue; } break; }",
+                "for (c in \"abc\") { while (c) { continue; } break; }",
+            ),
+            ("while (x) {}; x", "while (x) {} x"),
+            (
+                "while (x) { let f = fn() { while (y) { break; } }; }",
+                "while (x) { let f = fn() { while (y) { break; } }; }",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_loop_errors() -> Result<(), ()> {
+        let tests = [
+            ("break;", "1:1: break outside of a loop"),
+            ("if (x) {\n  continue\n}", "2:3: continue outside of a loop"),
+            (
+                "while (x) { fn() { break; } }",
+                "1:20: break outside of a loop",
+            ),
+            ("while (x) {} break", "1:14: break outside of a loop"),
+            (
+                "while x {}",
+                "1:7: expected next token to be LParen, got Ident instead",
+            ),
+            (
+                "for (1 in xs) {}",
+                "1:6: expected next token to be Ident, got Int instead",
+            ),
+            (
+                "for (x xs) {}",
+                "1:8: expected next token to be In, got Ident instead",
+            ),
+            (
+                "for (x in xs) x",
+                "1:15: expected next token to be LBrace, got Ident instead",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+
+            if parser.errors().first().map(String::as_str) != Some(expected) {
+                println!("unexpected errors: {:?}", parser.errors());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_literal_parsing() -> Result<(), ()> {
+        let lexer = Lexer::new("fn(x, y) { x + y; }");
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        let function = match &program.statements[..] {
+            [Statement::Expression(ExpressionStatement {
+                expression: Some(Expression::FunctionLiteral(function)),
+                ..
+            })] => function,
+            _ => {
+                println!("program is not a single FunctionLiteral");
+                return Err(());
+            }
+        };
+
+        let parameters: Vec<&str> = function
+            .parameters
+            .iter()
+            .map(|p| p.value.as_str())
+            .collect();
+        if parameters != ["x", "y"] {
+            println!("parameters are not [x, y]. got {:?}", parameters);
+            return Err(());
+        }
+
+        if function.body.string() != "{ (x + y) }" {
+            println!("body is not {{ (x + y) }}. got {}", function.body.string());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_parameter_parsing() -> Result<(), ()> {
+        let tests = [
+            ("fn() {};", vec![]),
+            ("fn(x) {};", vec!["x"]),
+            ("fn(x, y, z) {};", vec!["x", "y", "z"]),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            let parameters: Vec<&str> = match &program.statements[..] {
+                [Statement::Expression(ExpressionStatement {
+                    expression: Some(Expression::FunctionLiteral(function)),
+                    ..
+                })] => function
+                    .parameters
+                    .iter()
+                    .map(|p| p.value.as_str())
+                    .collect(),
+                _ => {
+                    println!("program is not a single FunctionLiteral: {}", input);
+                    return Err(());
+                }
+            };
+
+            if parameters != expected {
+                println!("expected={:?} got={:?}", expected, parameters);
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_expression_parsing() -> Result<(), ()> {
+        let lexer = Lexer::new("add(1, 2 * 3, 4 + 5);");
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        let call = match &program.statements[..] {
+            [Statement::Expression(ExpressionStatement {
+                expression: Some(Expression::CallExpression(call)),
+                ..
+            })] => call,
+            _ => {
+                println!("program is not a single CallExpression");
+                return Err(());
+            }
+        };
+
+        if call.function.string() != "add" {
+            println!("function is not add. got {}", call.function.string());
+            return Err(());
+        }
+
+        let arguments: Vec<String> = call.arguments.iter().map(|a| a.string()).collect();
+        if arguments != ["1", "(2 * 3)", "(4 + 5)"] {
+            println!("wrong arguments. got {:?}", arguments);
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_errors() -> Result<(), ()> {
+        let tests = [
+            (
+                "fn(x, 1) { x }",
+                "1:7: expected next token to be Ident, got Int instead",
+            ),
+            (
+                "fn(x y) { x }",
+                "1:6: expected next token to be RParen, got Ident instead",
+            ),
+            (
+                "fn(x) x",
+                "1:7: expected next token to be LBrace, got Ident instead",
+            ),
+            (
+                "add(1, 2",
+                "1:9: expected ) to close call started at 1:4, got Eof instead",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+
+            if parser.errors().first().map(String::as_str) != Some(expected) {
+                println!("unexpected errors: {:?}", parser.errors());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_literal_parsing() -> Result<(), ()> {
+        let lexer = Lexer::new("[1, 2 * 2, \"three\"]");
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        let array = match &program.statements[..] {
+            [Statement::Expression(ExpressionStatement {
+                expression: Some(Expression::ArrayLiteral(array)),
+                ..
+            })] => array,
+            _ => {
+                println!("program is not a single ArrayLiteral");
                 return Err(());
             }
         };
 
-        let ident = match expr {
-            Expression::Identifier(ident) => ident,
+        let elements: Vec<String> = array.elements.iter().map(|e| e.string()).collect();
+        if elements != ["1", "(2 * 2)", "\"three\""] {
+            println!("wrong elements. got {:?}", elements);
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_literals() -> Result<(), ()> {
+        let tests = [
+            ("[]", "[]"),
+            ("[1]", "[1]"),
+            ("[1,]", "[1]"),
+            ("[1, 2, 3,]", "[1, 2, 3]"),
+            ("[\n  1,\n  [2, 3],\n]", "[1, [2, 3]]"),
+            (
+                "let xs = [fn(x) { x }, true];",
+                "let xs = [fn(x) { x }, true];",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_expression_parsing() -> Result<(), ()> {
+        let lexer = Lexer::new("my_array[1 + 1]");
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        let index = match &program.statements[..] {
+            [Statement::Expression(ExpressionStatement {
+                expression: Some(Expression::IndexExpression(index)),
+                ..
+            })] => index,
             _ => {
-                println!("Expression not an Identifier");
+                println!("program is not a single IndexExpression");
                 return Err(());
             }
         };
 
-        if ident.value != String::from("foobar") {
-            println!("ident.value is not {}. Got {}", "foobar", ident.value);
+        if index.left.string() != "my_array" {
+            println!("left is not my_array. got {}", index.left.string());
             return Err(());
         }
 
-        if ident.token_literal() != String::from("foobar") {
-            println!(
-                "ident.token_literal is not {}. Got: {}",
-                "foobar",
-                ident.token_literal()
-            );
+        if index.index.string() != "(1 + 1)" {
+            println!("index is not (1 + 1). got {}", index.index.string());
             return Err(());
         }
 
@@ -390,84 +1933,268 @@ mod test {
     }
 
     #[test]
-    fn test_integer_literal_expression() -> Result<(), ()> {
-        let input = String::from("5;");
-        let lexer = Lexer::new(input);
+    fn test_hash_literal_parsing() -> Result<(), ()> {
+        let lexer = Lexer::new("{\"name\": \"x\", 1: true, flag: 2 + 3}");
         let mut parser = Parser::new(lexer);
         let program = parser.parse_program();
         check_parser_errors(&mut parser)?;
 
-        if program.statements.len() != 1 {
-            println!(
-                "program doesn't have enough statements, got: {}",
-                program.statements.len()
-            );
+        let hash = match &program.statements[..] {
+            [Statement::Expression(ExpressionStatement {
+                expression: Some(Expression::HashLiteral(hash)),
+                ..
+            })] => hash,
+            _ => {
+                println!("program is not a single HashLiteral");
+                return Err(());
+            }
+        };
+
+        let pairs: Vec<(String, String)> = hash
+            .pairs
+            .iter()
+            .map(|(key, value)| (key.string(), value.string()))
+            .collect();
+        let expected = [("\"name\"", "\"x\""), ("1", "true"), ("flag", "(2 + 3)")];
+
+        if pairs.len() != expected.len()
+            || pairs
+                .iter()
+                .zip(expected)
+                .any(|(got, want)| got.0 != want.0 || got.1 != want.1)
+        {
+            println!("wrong pairs. got {:?}", pairs);
             return Err(());
         }
 
-        let stmt = match &program.statements[0] {
-            Statement::Expression(expr_stmt) => expr_stmt,
-            _ => {
-                println!("Statement is not an ExpressionStatement");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_literals() -> Result<(), ()> {
+        let tests = [
+            ("{}", "{}"),
+            ("{\"b\": 1, \"a\": 2}", "{\"b\": 1, \"a\": 2}"),
+            ("{1: 2,}", "{1: 2}"),
+            (
+                "{\n  \"a\": [1, 2],\n  \"b\": {\"c\": 3},\n}",
+                "{\"a\": [1, 2], \"b\": {\"c\": 3}}",
+            ),
+            ("{\"a\": 1}[\"a\"]", "({\"a\": 1}[\"a\"])"),
+            ("let h = {x: fn(y) { y }};", "let h = {x: fn(y) { y }};"),
+            ("if (x) { {} }", "if (x) { {} }"),
+            ("fn() { {1: 2} }", "fn() { {1: 2} }"),
+            ("\"${ {\"a\": 1}[\"a\"] }\"", "\"${({\"a\": 1}[\"a\"])}\""),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
                 return Err(());
             }
-        };
+        }
 
-        let stmt_expr = match &stmt.expression {
-            Some(e) => e,
-            None => {
-                println!("Statment not expression");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_literal_errors() -> Result<(), ()> {
+        let tests = [
+            (
+                "{1: 2",
+                "1:6: expected } to close hash started at 1:1, got Eof instead",
+            ),
+            (
+                "{ x; y }",
+                "1:1: blocks are only allowed after if, fn, while or for",
+            ),
+            (
+                "{ x }",
+                "1:1: blocks are only allowed after if, fn, while or for",
+            ),
+            (
+                "let a = { let b = {\"${c}\": [d]}; b };",
+                "1:9: blocks are only allowed after if, fn, while or for",
+            ),
+            (
+                "{1, 2}",
+                "1:1: blocks are only allowed after if, fn, while or for",
+            ),
+            (
+                "{1: 2, 3}",
+                "1:9: expected next token to be Colon, got RBrace instead",
+            ),
+            (
+                "{1: 2, 3: 4",
+                "1:12: expected } to close hash started at 1:1, got Eof instead",
+            ),
+            (
+                "{1: 2 3: 4}",
+                "1:7: expected next token to be RBrace, got Int instead",
+            ),
+            (
+                "{1: 2,, 3: 4}",
+                "1:7: expected an expression, got Comma instead",
+            ),
+            (
+                "{1:}",
+                "1:4: no prefix parse function for RBrace \"}\" found",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+
+            if parser.errors().first().map(String::as_str) != Some(expected) {
+                println!("unexpected errors: {:?}", parser.errors());
                 return Err(());
             }
-        };
+        }
 
-        let expr = match stmt_expr {
-            Expression::IntegerLiteral(literal) => literal,
-            _ => {
-                println!("Something went wrong");
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_errors() -> Result<(), ()> {
+        let tests = [
+            (
+                "[1, 2",
+                "1:6: expected ] to close list started at 1:1, got Eof instead",
+            ),
+            (
+                "let a = [\n  1,\n",
+                "3:1: expected ] to close list started at 1:9, got Eof instead",
+            ),
+            ("[,]", "1:2: expected an expression, got Comma instead"),
+            ("[1,, 2]", "1:4: expected an expression, got Comma instead"),
+            ("f(,)", "1:3: expected an expression, got Comma instead"),
+            (
+                "[1 2]",
+                "1:4: expected next token to be RBracket, got Int instead",
+            ),
+            ("a[]", "1:3: expected an index between [ and ], got nothing"),
+            (
+                "a[1",
+                "1:4: expected ] to close index started at 1:2, got Eof instead",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+
+            if parser.errors().first().map(String::as_str) != Some(expected) {
+                println!("unexpected errors: {:?}", parser.errors());
                 return Err(());
             }
-        };
+        }
 
-        if expr.value != 5 {
-            println!("Expected literal value to be {} but got {}", 5, expr.value);
+        Ok(())
+    }
+
+    #[test]
+    fn test_numeric_literals() -> Result<(), ()> {
+        let int_tests = vec![
+            ("0xFF", 255),
+            ("0o755", 493),
+            ("0b1010", 10),
+            ("1_000_000", 1_000_000),
+            ("9223372036854775807", i64::MAX),
+        ];
+
+        for (input, expected) in int_tests {
+            let mut parser = Parser::new(Lexer::new(input));
+
+            match parser.parse_integer_literal() {
+                Some(Expression::IntegerLiteral(literal)) if literal.value == expected => {}
+                _ => {
+                    println!("{} did not parse to {}", input, expected);
+                    return Err(());
+                }
+            }
+            check_parser_errors(&mut parser)?;
         }
 
-        if expr.token_literal() != "5".to_owned() {
-            println!(
-                "Expected token literal to be {} but got {}",
-                "5",
-                expr.token_literal()
-            );
-            return Err(());
+        let float_tests = vec![
+            ("2.75", 2.75),
+            ("1e-9", 1e-9),
+            ("2.5E+3", 2500.0),
+            ("1_0.5", 10.5),
+        ];
+
+        for (input, expected) in float_tests {
+            let mut parser = Parser::new(Lexer::new(input));
+
+            match parser.parse_float_literal() {
+                Some(Expression::FloatLiteral(literal)) if literal.value == expected => {}
+                _ => {
+                    println!("{} did not parse to {}", input, expected);
+                    return Err(());
+                }
+            }
+            check_parser_errors(&mut parser)?;
         }
 
         Ok(())
     }
 
     #[test]
-    fn test_prefix_operators() -> Result<(), ()> {
-        struct PrefixTests {
-            input: String,
-            operator: String,
-            integer_value: i64,
+    fn test_numeric_literal_out_of_range() -> Result<(), ()> {
+        let mut parser = Parser::new(Lexer::new("\n  9223372036854775808"));
+
+        if parser.parse_integer_literal().is_some() {
+            return Err(());
         }
 
-        let prefix_tests = vec![
-            PrefixTests {
-                input: String::from("!5;"),
-                operator: String::from("!"),
-                integer_value: 5,
-            },
-            PrefixTests {
-                input: String::from("-15;"),
-                operator: String::from("-"),
-                integer_value: 15,
-            },
+        if parser.errors() != &vec!["2:3: integer literal 9223372036854775808 is out of range"] {
+            println!("unexpected errors: {:?}", parser.errors());
+            return Err(());
+        }
+
+        let mut parser = Parser::new(Lexer::new("0xFFFFFFFFFFFFFFFFF 1e999"));
+
+        if parser.parse_integer_literal().is_some() {
+            return Err(());
+        }
+        parser.next_token();
+        if parser.parse_float_literal().is_some() {
+            return Err(());
+        }
+
+        let expected = vec![
+            "1:1: integer literal 0xFFFFFFFFFFFFFFFFF is out of range",
+            "1:21: float literal 1e999 is out of range",
         ];
 
-        for tt in prefix_tests.iter() {
-            let lexer = Lexer::new(tt.input.clone());
+        if parser.errors() != &expected {
+            println!("unexpected errors: {:?}", parser.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parsing_infix_expressions() -> Result<(), ()> {
+        let infix_tests = [
+            ("5 + 5;", 5, "+", 5),
+            ("5 - 5;", 5, "-", 5),
+            ("5 * 5;", 5, "*", 5),
+            ("5 / 5;", 5, "/", 5),
+            ("5 > 5;", 5, ">", 5),
+            ("5 < 5;", 5, "<", 5),
+            ("5 == 5;", 5, "==", 5),
+            ("5 != 5;", 5, "!=", 5),
+        ];
+
+        for (input, left_value, operator, right_value) in infix_tests {
+            let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
             let program = parser.parse_program();
             check_parser_errors(&mut parser)?;
@@ -480,44 +2207,256 @@ mod test {
                 return Err(());
             }
 
-            let stmt = match &program.statements[0] {
-                Statement::Expression(expr_stmt) => expr_stmt,
+            let expr = match &program.statements[0] {
+                Statement::Expression(ExpressionStatement {
+                    expression: Some(Expression::InfixExpression(expr)),
+                    ..
+                }) => expr,
                 _ => {
-                    println!("Statement is not an ExpressionStatement");
+                    println!("{} is not an InfixExpression", input);
                     return Err(());
                 }
             };
 
-            let stmt_expr = match &stmt.expression {
-                Some(e) => e,
-                None => {
-                    println!("Statment not expression");
-                    return Err(());
+            if expr.operator != operator {
+                println!(
+                    "Expressions operator is not {}. got {}",
+                    operator, expr.operator
+                );
+                return Err(());
+            }
+
+            for (side, value) in [(&expr.left, left_value), (&expr.right, right_value)] {
+                match side.as_ref() {
+                    Expression::IntegerLiteral(literal) if test_integer_literal(literal, value) => {
+                    }
+                    _ => return Err(()),
                 }
-            };
+            }
+        }
 
-            let expr = match stmt_expr {
-                Expression::PrefixExpression(prefix_expr) => prefix_expr,
+        Ok(())
+    }
+
+    #[test]
+    fn test_operator_precedence_parsing() -> Result<(), ()> {
+        let tests = [
+            ("-a * b", "((-a) * b)"),
+            ("!-a", "(!(-a))"),
+            ("a + b + c", "((a + b) + c)"),
+            ("a + b - c", "((a + b) - c)"),
+            ("a * b * c", "((a * b) * c)"),
+            ("a * b / c", "((a * b) / c)"),
+            ("a + b / c", "(a + (b / c))"),
+            ("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f)"),
+            ("3 + 4; -5 * 5", "(3 + 4)((-5) * 5)"),
+            ("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4))"),
+            ("5 < 4 != 3 > 4", "((5 < 4) != (3 > 4))"),
+            (
+                "3 + 4 * 5 == 3 * 1 + 4 * 5",
+                "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)))",
+            ),
+            ("a - -b", "(a - (-b))"),
+            ("-a ** 2", "(-(a ** 2))"),
+            ("!a && b", "((!a) && b)"),
+            ("true", "true"),
+            ("false", "false"),
+            ("3 > 5 == false", "((3 > 5) == false)"),
+            ("3 < 5 == true", "((3 < 5) == true)"),
+            ("1 + (2 + 3) + 4", "((1 + (2 + 3)) + 4)"),
+            ("(5 + 5) * 2", "((5 + 5) * 2)"),
+            ("2 / (5 + 5)", "(2 / (5 + 5))"),
+            ("-(5 + 5)", "(-(5 + 5))"),
+            ("!(true == false)", "(!(true == false))"),
+            ("(a ** b) ** c", "((a ** b) ** c)"),
+            ("((a))", "a"),
+            ("a + add(b * c) + d", "((a + add((b * c))) + d)"),
+            (
+                "add(a, b, 1, 2 * 3, 4 + 5, add(6, 7 * 8))",
+                "add(a, b, 1, (2 * 3), (4 + 5), add(6, (7 * 8)))",
+            ),
+            (
+                "add(a + b + c * d / f + g)",
+                "add((((a + b) + ((c * d) / f)) + g))",
+            ),
+            ("-f(x)", "(-f(x))"),
+            ("f(x)(y)", "f(x)(y)"),
+            ("fn(x){x}(5)", "fn(x) { x }(5)"),
+            (
+                "let add = fn(a, b) { a + b };",
+                "let add = fn(a, b) { (a + b) };",
+            ),
+            ("add(1, 2,)", "add(1, 2)"),
+            (
+                "a * [1, 2, 3, 4][b * c] * d",
+                "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+            ),
+            (
+                "add(a * b[2], b[1], 2 * [1, 2][1])",
+                "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
+            ),
+            ("-a[0]", "(-(a[0]))"),
+            ("a[0][1]", "((a[0])[1])"),
+            ("f(x)[0]", "(f(x)[0])"),
+            ("fns[0](x)", "(fns[0])(x)"),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_operators() -> Result<(), ()> {
+        let tests = vec![
+            ("a <= b", "(a <= b)"),
+            ("a >= b", "(a >= b)"),
+            ("a % b", "(a % b)"),
+            ("a ** b", "(a ** b)"),
+            ("a && b", "(a && b)"),
+            ("a || b", "(a || b)"),
+            ("a + b % c", "(a + (b % c))"),
+            ("a * b ** c", "(a * (b ** c))"),
+            ("a ** b ** c", "(a ** (b ** c))"),
+            ("a <= b == c >= d", "((a <= b) == (c >= d))"),
+            ("a || b && c", "(a || (b && c))"),
+            ("a && b || c && d", "((a && b) || (c && d))"),
+            ("a == b && c != d", "((a == b) && (c != d))"),
+            ("x += 1", "(x += 1)"),
+            ("x -= y * 2", "(x -= (y * 2))"),
+            ("x *= y /= 2", "(x *= (y /= 2))"),
+            ("x /= a || b", "(x /= (a || b))"),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() -> Result<(), ()> {
+        let lexer = Lexer::new("a + b += 1");
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        let expected = "1:7: cannot assign to (a + b) with +=";
+        if parser.errors().first().map(String::as_str) != Some(expected) {
+            println!("unexpected errors: {:?}", parser.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_literals() -> Result<(), ()> {
+        let tests = vec![
+            (r#""plain""#, r#""plain""#),
+            (r#""tab\there\n""#, r#""tab\there\n""#),
+            (r#""\u{41}\x42""#, r#""AB""#),
+            (
+                r#""hello ${name}, you have ${len + 1} items""#,
+                r#""hello ${name}, you have ${(len + 1)} items""#,
+            ),
+            (r#""${a}${b}""#, r#""${a}${b}""#),
+            (r#""a ${"b ${c}"} \${d} $e""#, r#""a ${"b ${c}"} \${d} $e""#),
+            (r#""${"\"quoted\""}""#, r#""${"\"quoted\""}""#),
+        ];
+
+        for (input, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_char_literals() -> Result<(), ()> {
+        let tests = [
+            (r"'a'", 'a', "'a'"),
+            (r"'\u{00e9}'", 'é', "'é'"),
+            (r"'\n'", '\n', r"'\n'"),
+            (r"'\''", '\'', r"'\''"),
+            (r#"'"'"#, '"', r#"'"'"#),
+        ];
+
+        for (input, value, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&mut parser)?;
+
+            match &program.statements[0] {
+                Statement::Expression(ExpressionStatement {
+                    expression: Some(Expression::CharLiteral(literal)),
+                    ..
+                }) if literal.value == value => {}
                 _ => {
-                    println!("Something went wrong");
+                    println!("expected {:?} to parse to the char {:?}", input, value);
                     return Err(());
                 }
-            };
+            }
 
-            if expr.operator != tt.operator {
-                println!(
-                    "Expressions operator is not {}. got {}",
-                    tt.operator, expr.operator
-                );
+            if program.string() != expected {
+                println!("expected={} got={}", expected, program.string());
                 return Err(());
             }
+        }
 
-            let right = match *expr.right {
-                Expression::IntegerLiteral(ref literal) => literal.clone(),
-                _ => return Err(()),
-            };
+        Ok(())
+    }
 
-            if !test_integer_literal(&right, tt.integer_value) {
+    #[test]
+    fn test_interpolated_string_parts() -> Result<(), ()> {
+        let lexer = Lexer::new(r#""hello ${name}!""#);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&mut parser)?;
+
+        let parts = match &program.statements[0] {
+            Statement::Expression(ExpressionStatement {
+                expression: Some(Expression::InterpolatedString(interpolated)),
+                ..
+            }) => &interpolated.parts,
+            _ => {
+                println!("statement is not an InterpolatedString");
+                return Err(());
+            }
+        };
+
+        match parts.as_slice() {
+            [InterpolationPart::Literal(hello), InterpolationPart::Expression(Expression::Identifier(name)), InterpolationPart::Literal(bang)]
+                if hello == "hello " && name.value == "name" && bang == "!" => {}
+            _ => {
+                println!("unexpected parts");
                 return Err(());
             }
         }
@@ -525,6 +2464,21 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_unclosed_interpolation() -> Result<(), ()> {
+        let lexer = Lexer::new("\"a ${b c}\"");
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        let expected = "1:8: expected } to close interpolation started at 1:1, got Ident instead";
+        if parser.errors().first().map(String::as_str) != Some(expected) {
+            println!("unexpected errors: {:?}", parser.errors());
+            return Err(());
+        }
+
+        Ok(())
+    }
+
     fn test_integer_literal(il: &IntegerLiteral, value: i64) -> bool {
         if il.value != value {
             println!("integer value is not {}. got: {}", value, il.value);
@@ -1,5 +1,4 @@
 use crate::lexer::Lexer;
-use crate::tokens::{Token, TokenType};
 use std::io::Write;
 use std::io::{stdin, stdout};
 
@@ -19,17 +18,15 @@ impl Repl {
                 .read_line(&mut input)
                 .expect("Error reading from stdin");
 
-            let mut lexer = Lexer::new(input);
-
-            loop {
-                let token = lexer.next_token();
-
-                if token == Token::new(TokenType::Eof, "".into()) {
-                    break;
-                }
+            let mut lexer = Lexer::with_file(&input, "<repl>");
 
+            for token in lexer.by_ref() {
                 println!("{:?}", token);
             }
+
+            for error in lexer.errors() {
+                println!("error: {}", error);
+            }
         }
     }
 }
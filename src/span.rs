@@ -0,0 +1,72 @@
+use std::fmt;
+use std::rc::Rc;
+
+/// A single point in the source text.
+///
+/// `line` and `column` are 1-based, with `column` counted in characters.
+/// `offset` is the 0-based byte offset.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(offset: usize, line: usize, column: usize) -> Position {
+        Position {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    /// The position just past `text`, when `text` starts at this position.
+    pub fn advance(self, text: &str) -> Position {
+        let mut position = self;
+        position.offset += text.len();
+
+        for ch in text.chars() {
+            if ch == '\n' {
+                position.line += 1;
+                position.column = 1;
+            } else {
+                position.column += 1;
+            }
+        }
+
+        position
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::new(0, 1, 1)
+    }
+}
+
+/// The region of source a token (or anything built from tokens) came from.
+///
+/// `end` is exclusive, so `start.offset..end.offset` is the byte range.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Span {
+    pub file: Option<Rc<str>>,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(file: Option<Rc<str>>, start: Position, end: Position) -> Span {
+        Span { file, start, end }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+
+        write!(f, "{}:{}", self.start.line, self.start.column)
+    }
+}
@@ -0,0 +1,296 @@
+use crate::lexer::{LexError, LexErrorKind, Lexer};
+use crate::span::{Position, Span};
+use crate::tokens::{Token, TokenType};
+use std::io::BufRead;
+use std::rc::Rc;
+
+/// Lexes a program pulled from a reader on demand instead of from a `String`
+/// held in memory.
+///
+/// Input is read a line at a time. A token is only handed out once the
+/// buffered text extends past it, so tokens that run over several lines
+/// (strings, block comments) are re-lexed with more input until they are
+/// complete. Each retry at least doubles the text waiting to be lexed, which
+/// keeps the total work linear however long the token is.
+///
+/// Spans are relative to the whole stream. Invalid UTF-8 is lexed as U+FFFD,
+/// but span offsets still count the bytes that were actually read.
+pub struct StreamLexer<R: BufRead> {
+    pub reader: R,
+    pub file: Option<Rc<str>>,
+    pub errors: Vec<LexError>,
+
+    buffer: String,
+    /// Bytes of `buffer` already turned into tokens.
+    cursor: usize,
+    /// Where `buffer[cursor..]` starts in the stream.
+    start: Position,
+    /// Where `buffer` ends in the stream.
+    end: Position,
+    /// Interpolations open at `start`, see `Lexer::templates`.
+    templates: Vec<(Position, usize)>,
+    /// For each U+FFFD put in place of invalid input: the offset just past
+    /// it, and how many bytes longer the text is than the stream up to there.
+    /// `start` and `end` count text bytes; these map them back.
+    substitutions: Vec<(usize, usize)>,
+    done: bool,
+}
+
+impl<R: BufRead> StreamLexer<R> {
+    pub fn new(reader: R) -> Self {
+        StreamLexer {
+            reader,
+            file: None,
+            errors: Vec::new(),
+            buffer: String::new(),
+            cursor: 0,
+            start: Position::default(),
+            end: Position::default(),
+            templates: Vec::new(),
+            substitutions: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Creates a lexer whose token spans report `file` as their origin.
+    pub fn with_file(reader: R, file: &str) -> Self {
+        let mut l = Self::new(reader);
+        l.file = Some(file.into());
+        l
+    }
+
+    pub fn next_token(&mut self) -> Token<'static> {
+        loop {
+            let mut lexer = Lexer::resume(&self.buffer[self.cursor..], self.start);
+            lexer.file = self.file.clone();
+            lexer.templates = self.templates.clone();
+
+            let token = lexer.next_token();
+            let consumed = token.span.end.offset - self.start.offset;
+            let complete =
+                token.token_type != TokenType::Eof && self.cursor + consumed < self.buffer.len();
+
+            if complete || self.done {
+                for mut error in lexer.errors.drain(..) {
+                    to_stream(&self.substitutions, &mut error.span);
+                    self.errors.push(error);
+                }
+
+                self.cursor += consumed;
+                self.start = token.span.end;
+                self.templates = lexer.templates;
+
+                let mut token = token.into_owned();
+                to_stream(&self.substitutions, &mut token.span);
+                return token;
+            }
+
+            self.fill();
+        }
+    }
+
+    pub fn errors(&self) -> &Vec<LexError> {
+        &self.errors
+    }
+
+    /// Appends at least as much input as is already buffered, dropping the
+    /// text that has already been lexed.
+    fn fill(&mut self) {
+        self.buffer.drain(..self.cursor);
+        self.cursor = 0;
+
+        let want = self.buffer.len().max(1);
+        let mut read = 0;
+
+        while read < want && !self.done {
+            read += self.read_line();
+        }
+    }
+
+    /// Appends the next line of input to the buffer, returning how many bytes
+    /// were read.
+    fn read_line(&mut self) -> usize {
+        let mut line = Vec::new();
+
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => self.done = true,
+            Ok(len) => {
+                let mut reported = false;
+
+                for chunk in line.utf8_chunks() {
+                    self.push(chunk.valid());
+
+                    if chunk.invalid().is_empty() {
+                        continue;
+                    }
+
+                    if !reported {
+                        self.error(LexErrorKind::InvalidUtf8);
+                        reported = true;
+                    }
+
+                    self.push("\u{FFFD}");
+                    let extra = self.substitutions.last().map_or(0, |&(_, extra)| extra);
+                    let extra = extra + '\u{FFFD}'.len_utf8() - chunk.invalid().len();
+                    self.substitutions.push((self.end.offset, extra));
+                }
+
+                return len;
+            }
+            Err(err) => {
+                self.done = true;
+                self.error(LexErrorKind::Io(err.to_string()));
+            }
+        }
+
+        0
+    }
+
+    fn push(&mut self, text: &str) {
+        self.buffer.push_str(text);
+        self.end = self.end.advance(text);
+    }
+
+    /// Records an error at the end of the text read so far.
+    fn error(&mut self, kind: LexErrorKind) {
+        let mut span = Span::new(self.file.clone(), self.end, self.end);
+        to_stream(&self.substitutions, &mut span);
+        self.errors.push(LexError { kind, span });
+    }
+}
+
+/// Turns the text offsets in `span` into byte offsets in the stream.
+fn to_stream(substitutions: &[(usize, usize)], span: &mut Span) {
+    for position in [&mut span.start, &mut span.end] {
+        let i = substitutions.partition_point(|&(end, _)| end <= position.offset);
+
+        if i > 0 {
+            position.offset -= substitutions[i - 1].1;
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for StreamLexer<R> {
+    type Item = Token<'static>;
+
+    /// Yields every token up to, but not including, `Eof`.
+    fn next(&mut self) -> Option<Token<'static>> {
+        let token = self.next_token();
+
+        if token.token_type == TokenType::Eof {
+            None
+        } else {
+            Some(token)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StreamLexer;
+    use crate::lexer::{LexErrorKind, Lexer};
+    use crate::span::Position;
+    use std::io::{BufReader, Cursor};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn matches_in_memory_lexer() -> Result<(), ()> {
+        let input = "let s = \"first line
+second line\";
+/* a block comment
+   over /* nested */ lines */ let x = 0x_FF >= 1e3;
+/// docs
+let größe = \"é\\u{1F600} ${ {
+    x
+} } and ${y}\" // trailing
+\"never closed";
+
+        let expected: Vec<_> = Lexer::with_file(input, "gen.mk").collect();
+
+        // A tiny buffer makes sure reads stop part way through lines too.
+        let reader = BufReader::with_capacity(4, input.as_bytes());
+        let mut lexer = StreamLexer::with_file(reader, "gen.mk");
+        let got: Vec<_> = lexer.by_ref().collect();
+
+        if got != expected {
+            println!("expected: {:#?}\ngot: {:#?}", expected, got);
+            return Err(());
+        }
+
+        let mut in_memory = Lexer::with_file(input, "gen.mk");
+        in_memory.by_ref().for_each(drop);
+
+        if lexer.errors() != in_memory.errors() {
+            println!(
+                "expected errors: {:?}\ngot: {:?}",
+                in_memory.errors(),
+                lexer.errors()
+            );
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_utf8() -> Result<(), ()> {
+        let input: &[u8] = b"let a = 1;\nlet b = \"\xff\xe2\x82\";\nlet c = b;\n";
+
+        let mut lexer = StreamLexer::new(Cursor::new(input));
+        let tokens: Vec<_> = lexer.by_ref().collect();
+
+        if tokens[8].literal != "\u{FFFD}\u{FFFD}" {
+            println!("got: {:?}", tokens[8]);
+            return Err(());
+        }
+
+        // Every other token's span must still pick its text out of the raw bytes.
+        for (i, token) in tokens.iter().enumerate() {
+            let bytes = &input[token.span.start.offset..token.span.end.offset];
+
+            if i != 8 && bytes != token.literal.as_bytes() {
+                println!("span of {:?} covers {:?}", token, bytes);
+                return Err(());
+            }
+        }
+
+        let errors = lexer.errors();
+        if errors.len() != 1
+            || errors[0].kind != LexErrorKind::InvalidUtf8
+            || errors[0].span.start != Position::new(20, 2, 10)
+        {
+            println!("unexpected errors: {:?}", errors);
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn long_multiline_tokens() -> Result<(), ()> {
+        let lines = "    a line of text\n".repeat(20_000);
+        let input = format!(
+            "let a = \"\"\"\n{}    \"\"\";\nlet b = \"\n{}\";\n/*\n{}*/ a",
+            lines, lines, lines
+        );
+
+        let expected: Vec<_> = Lexer::new(&input).collect();
+
+        // Re-lexing the unfinished token once per line read took minutes here.
+        let start = Instant::now();
+        let got: Vec<_> = StreamLexer::new(Cursor::new(input.as_bytes())).collect();
+        let elapsed = start.elapsed();
+
+        if got != expected {
+            println!("streamed tokens differ from the in-memory lexer");
+            return Err(());
+        }
+
+        if elapsed > Duration::from_secs(5) {
+            println!("took {:?}", elapsed);
+            return Err(());
+        }
+
+        Ok(())
+    }
+}
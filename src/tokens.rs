@@ -1,14 +1,29 @@
+use crate::span::Span;
+use std::borrow::Cow;
+
+/// A token whose literal borrows from the source it was lexed from where
+/// possible. Use `into_owned` to keep it around longer than the source.
 #[derive(Debug, PartialEq, Clone)]
-pub struct Token {
+pub struct Token<'src> {
     pub token_type: TokenType,
-    pub literal: String,
+    pub literal: Cow<'src, str>,
+    pub span: Span,
 }
 
-impl Token {
-    pub fn new(token_type: TokenType, literal: String) -> Token {
+impl<'src> Token<'src> {
+    pub fn new(token_type: TokenType, literal: Cow<'src, str>) -> Token<'src> {
         Token {
             token_type,
             literal,
+            span: Span::default(),
+        }
+    }
+
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            token_type: self.token_type,
+            literal: Cow::Owned(self.literal.into_owned()),
+            span: self.span,
         }
     }
 }
@@ -19,14 +34,18 @@ pub enum TokenType {
     Eof,
     Ident,
     Int,
+    Float,
     Assign,
     Plus,
     Comma,
     Semicolon,
+    Colon,
     LParen,
     RParen,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
     Function,
     Let,
     Equal,
@@ -40,7 +59,62 @@ pub enum TokenType {
     Slash,
     LessThan,
     GreaterThan,
+    LessEqual,
+    GreaterEqual,
+    And,
+    Or,
+    Percent,
+    Power,
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
     If,
     Else,
+    While,
+    For,
+    In,
+    Break,
+    Continue,
+    Null,
+    Const,
+    Match,
+    Import,
     String,
+    Char,
+    TemplateHead,
+    TemplateMiddle,
+    TemplateTail,
+    DocComment,
+    Whitespace,
+    Comment,
+}
+
+/// Every reserved word and the token it lexes to. None of them can be used
+/// as an identifier, including those the parser does not understand yet.
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    ("fn", TokenType::Function),
+    ("let", TokenType::Let),
+    ("return", TokenType::Return),
+    ("true", TokenType::True),
+    ("false", TokenType::False),
+    ("if", TokenType::If),
+    ("else", TokenType::Else),
+    ("while", TokenType::While),
+    ("for", TokenType::For),
+    ("in", TokenType::In),
+    ("break", TokenType::Break),
+    ("continue", TokenType::Continue),
+    ("null", TokenType::Null),
+    ("const", TokenType::Const),
+    ("match", TokenType::Match),
+    ("import", TokenType::Import),
+];
+
+/// Returns the keyword token type for `ident`, if it is a reserved word.
+pub fn lookup_keyword(ident: &str) -> Option<TokenType> {
+    KEYWORDS
+        .iter()
+        .find(|(keyword, _)| *keyword == ident)
+        .map(|(_, token_type)| token_type.clone())
 }